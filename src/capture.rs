@@ -0,0 +1,132 @@
+use std::fs;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::framebuffer::Framebuffer;
+use crate::gif::GifWriter;
+use crate::present::{HeadlessPresenter, Presenter};
+
+const SCREENSHOT_DIR: &str = "capturas";
+// El juego corre a 60 cuadros por segundo
+const GAME_FPS: usize = 60;
+
+/// Dónde va una grabación en curso.
+enum Recording {
+    Sequence(String, HeadlessPresenter), // (carpeta, salida de los PNG numerados)
+    Gif(String, GifWriter),              // (archivo, GIF que se va escribiendo)
+}
+
+/// Capturas del frame ya compuesto (vista 3D, efectos, minimapa, HUD y texto):
+/// fotos sueltas con fecha y grabación cada N frames, como secuencia numerada o como GIF animado.
+pub struct Capture {
+    every: usize, // guardar 1 de cada `every` frames al grabar
+    gif: bool,    // grabar a un GIF en lugar de a PNG sueltos
+    recording: Option<Recording>,
+    frame: usize,
+}
+
+impl Capture {
+    pub fn new(every: usize, gif: bool) -> Self {
+        Capture { every: every.max(1), gif, recording: None, frame: 0 }
+    }
+
+    /// Guarda el frame en `capturas/captura_<fecha>.png`. Devuelve la ruta o el error.
+    pub fn screenshot(&self, framebuffer: &Framebuffer) -> Result<String, String> {
+        fs::create_dir_all(SCREENSHOT_DIR).map_err(|e| format!("{}: {}", SCREENSHOT_DIR, e))?;
+        let path = format!("{}/captura_{}.png", SCREENSHOT_DIR, timestamp());
+        if framebuffer.render_to_file(&path) {
+            Ok(path)
+        } else {
+            Err(format!("no se pudo escribir {}", path))
+        }
+    }
+
+    pub fn is_recording(&self) -> bool {
+        self.recording.is_some()
+    }
+
+    /// Empieza a grabar (en `capturas/secuencia_<fecha>/` o en `capturas/grabacion_<fecha>.gif`)
+    /// o termina la grabación en curso. `width` x `height` es el tamaño de los frames.
+    /// Devuelve un mensaje para mostrar en pantalla.
+    pub fn toggle_recording(&mut self, width: i32, height: i32) -> String {
+        match self.recording.take() {
+            Some(Recording::Sequence(dir, sequence)) => {
+                return format!("Grabación terminada: {} cuadros en {}", sequence.frames, dir);
+            }
+            Some(Recording::Gif(path, gif)) => {
+                let frames = gif.frames;
+                return match gif.finish() {
+                    Ok(()) => format!("Grabación terminada: {} cuadros en {}", frames, path),
+                    Err(e) => format!("No se pudo terminar {}: {}", path, e),
+                };
+            }
+            None => {}
+        }
+        if let Err(e) = fs::create_dir_all(SCREENSHOT_DIR) {
+            return format!("No se pudo grabar: {}: {}", SCREENSHOT_DIR, e);
+        }
+
+        let recording = if self.gif {
+            let path = format!("{}/grabacion_{}.gif", SCREENSHOT_DIR, timestamp());
+            // el GIF cuenta en centésimas de segundo
+            let delay = (self.every * 100 / GAME_FPS).max(2) as u16;
+            match GifWriter::create(&path, width, height, delay) {
+                Ok(gif) => Recording::Gif(path, gif),
+                Err(e) => return format!("No se pudo grabar: {}: {}", path, e),
+            }
+        } else {
+            let dir = format!("{}/secuencia_{}", SCREENSHOT_DIR, timestamp());
+            if let Err(e) = fs::create_dir_all(&dir) {
+                return format!("No se pudo grabar: {}: {}", dir, e);
+            }
+            let sequence = HeadlessPresenter::new(Some(format!("{}/cuadro_{{n}}.png", dir)));
+            Recording::Sequence(dir, sequence)
+        };
+        let target = match &recording {
+            Recording::Sequence(dir, _) | Recording::Gif(dir, _) => dir.clone(),
+        };
+        self.recording = Some(recording);
+        self.frame = 0;
+        format!("Grabando en {} (1 de cada {} cuadros)", target, self.every)
+    }
+
+    /// Llamar una vez por frame, con el frame completo: si se está grabando y toca, lo guarda.
+    pub fn record(&mut self, framebuffer: &Framebuffer) {
+        let Some(recording) = &mut self.recording else {
+            return;
+        };
+        if self.frame % self.every == 0 {
+            match recording {
+                Recording::Sequence(_, sequence) => sequence.present(framebuffer),
+                Recording::Gif(path, gif) => {
+                    if let Err(e) = gif.add_frame(framebuffer.pixels()) {
+                        eprintln!("{}: {}", path, e);
+                    }
+                }
+            }
+        }
+        self.frame += 1;
+    }
+}
+
+/// Fecha y hora actuales (UTC) como `AAAAMMDD_HHMMSS_mmm`, para nombres de archivo.
+fn timestamp() -> String {
+    let now = SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default();
+    let secs = now.as_secs();
+    let (days, rem) = ((secs / 86_400) as i64, secs % 86_400);
+
+    // días desde 1970-01-01 -> fecha civil (algoritmo de Howard Hinnant)
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z.rem_euclid(146_097);
+    let yoe = (doe - doe / 1_460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + if month <= 2 { 1 } else { 0 };
+
+    format!(
+        "{:04}{:02}{:02}_{:02}{:02}{:02}_{:03}",
+        year, month, day, rem / 3600, rem / 60 % 60, rem % 60, now.subsec_millis()
+    )
+}
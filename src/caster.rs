@@ -1,15 +1,27 @@
 use std::hint;
 
 use raylib::color::Color;
+use raylib::prelude::Vector2;
 
 use crate::framebuffer::Framebuffer;
 use crate::player::Player;
 use crate::maze::Maze;
 
+/// Cara de la celda golpeada por el rayo (N = borde superior, y pequeña).
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub enum Face {
+    North,
+    South,
+    East,
+    West,
+}
+
 pub struct Intersect {
     pub distance: f32,
     pub impact:char,
     pub tx: usize,
+    pub cell: (usize, usize), // (i, j) de la celda golpeada
+    pub face: Face,
 }
 
 pub fn cast_ray(
@@ -20,18 +32,36 @@ pub fn cast_ray(
     block_size: usize,
     draw_line: bool,
     ignore_goal: bool,
+) -> Intersect {
+    let intersect = cast_ray_from(maze, player.pos, a, block_size, ignore_goal);
+
+    if draw_line {
+        let end_x = player.pos.x + intersect.distance * a.cos();
+        let end_y = player.pos.y + intersect.distance * a.sin();
+        framebuffer.draw_line(player.pos.x as i32, player.pos.y as i32, end_x as i32, end_y as i32, Color::WHITE);
+    }
+
+    intersect
+}
+
+/// Igual que `cast_ray`, pero desde cualquier punto del mundo y sin dibujar nada
+/// (sirve para rayos de sombra de las luces o visibilidad entre entidades).
+pub fn cast_ray_from(
+    maze: &Maze,
+    origin: Vector2,
+    a: f32,
+    block_size: usize,
+    ignore_goal: bool,
 ) -> Intersect {
     let mut d = 0.0;
     let maze_height = maze.len();
     let maze_width = if maze_height > 0 { maze[0].len() } else { 0 };
 
-    framebuffer.set_current_color(Color::WHITE);
-
     loop {
         let cos = d * a.cos();
         let sin = d * a.sin();
-        let x = (player.pos.x + cos) as usize;
-        let y = (player.pos.y + sin) as usize;
+        let x = (origin.x + cos) as usize;
+        let y = (origin.y + sin) as usize;
 
         let i = x / block_size;
         let j = y / block_size;
@@ -50,7 +80,9 @@ pub fn cast_ray(
             return Intersect {
                 distance: d,
                 impact: ' ',  // Rayo sale del laberinto
-                tx: tx
+                tx: tx,
+                cell: (i, j),
+                face: hit_face(hitx, hity, block_size),
             };
         }
 
@@ -72,14 +104,12 @@ pub fn cast_ray(
             return Intersect {
                 distance: d,
                 impact: cell,  
-                tx: tx
+                tx: tx,
+                cell: (i, j),
+                face: hit_face(hitx, hity, block_size),
             };
         }
 
-        if draw_line {
-            framebuffer.set_pixel(x as i32, y as i32);
-        }
-
         d += 1.0;
 
         // Evitar que el rayo recorra una distancia demasiado larga
@@ -91,7 +121,21 @@ pub fn cast_ray(
     Intersect {
         distance: d,
         impact: ' ',
-        tx: 0
+        tx: 0,
+        cell: (0, 0),
+        face: Face::North,
+    }
+}
+
+/// Deduce la cara golpeada a partir del punto de impacto dentro de la celda
+/// (mismo criterio que `tx`: si hitx está lejos de los bordes, fue una cara horizontal).
+fn hit_face(hitx: usize, hity: usize, block_size: usize) -> Face {
+    if 1 < hitx && hitx < block_size - 1 {
+        if hity < block_size / 2 { Face::North } else { Face::South }
+    } else if hitx <= 1 {
+        Face::West
+    } else {
+        Face::East
     }
 }
 
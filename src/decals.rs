@@ -0,0 +1,161 @@
+use std::collections::HashMap;
+use std::fs;
+use crate::caster::Face;
+
+/// Calcomanía sobre una cara de pared: textura `key` dentro del rectángulo (u0,v0)-(u1,v1),
+/// en coordenadas de la cara (0..1, u de izquierda a derecha vista de frente, v hacia abajo).
+pub struct Decal {
+    pub key: char,
+    pub u0: f32,
+    pub v0: f32,
+    pub u1: f32,
+    pub v1: f32,
+}
+
+/// Lo que lleva una cara concreta: textura base propia (opcional) y calcomanías encima.
+#[derive(Default)]
+pub struct FaceArt {
+    pub base: Option<char>,
+    pub decals: Vec<Decal>,
+}
+
+/// Arte por cara de un piso, indexado por (i, j, cara).
+pub struct Decals {
+    faces: HashMap<(usize, usize, Face), FaceArt>,
+}
+
+impl Decals {
+    pub fn empty() -> Self {
+        Decals { faces: HashMap::new() }
+    }
+
+    /// Carga el arte por cara de `filename` (si no existe, el piso no tiene calcomanías).
+    /// Formato por línea (coordenadas en celdas, cara n|s|e|w, '#' = comentario):
+    ///   face  <i> <j> <cara> <textura>
+    ///   decal <i> <j> <cara> <textura> [u0 v0 u1 v1]
+    pub fn load(filename: &str) -> Self {
+        match fs::read_to_string(filename) {
+            Ok(text) => Decals::parse(filename, &text),
+            Err(_) => Decals::empty(),
+        }
+    }
+
+    /// Lee el texto de un archivo de arte por cara (`filename` es solo para los mensajes).
+    /// Las líneas inválidas se reportan y no dejan nada en el piso.
+    fn parse(filename: &str, text: &str) -> Self {
+        let mut decals = Decals::empty();
+
+        for (n, line) in text.lines().enumerate() {
+            let line = line.split('#').next().unwrap_or("").trim();
+            if line.is_empty() {
+                continue;
+            }
+            let parts: Vec<&str> = line.split_whitespace().collect();
+            let Some((cell, art)) = parse_line(&parts) else {
+                eprintln!("{}:{}: línea inválida: {}", filename, n + 1, line);
+                continue;
+            };
+            let face = decals.faces.entry(cell).or_default();
+            match art {
+                LineArt::Base(key) => face.base = Some(key),
+                LineArt::Decal(decal) => face.decals.push(decal),
+            }
+        }
+
+        decals
+    }
+
+    /// Arte de la cara (i, j, cara), si tiene alguno.
+    pub fn at(&self, cell: (usize, usize), face: Face) -> Option<&FaceArt> {
+        if self.faces.is_empty() {
+            return None;
+        }
+        self.faces.get(&(cell.0, cell.1, face))
+    }
+}
+
+/// Lo que agrega una línea válida a su cara.
+enum LineArt {
+    Base(char),
+    Decal(Decal),
+}
+
+/// Valida una línea entera: celda, cara, textura y, si lo trae, el rectángulo dentro de 0..1.
+fn parse_line(parts: &[&str]) -> Option<((usize, usize, Face), LineArt)> {
+    let (kind, rest) = parts.split_first()?;
+    let [i, j, side, key, rect @ ..] = rest else { return None };
+    let i: usize = i.parse().ok()?;
+    let j: usize = j.parse().ok()?;
+    let face = match *side {
+        "n" => Face::North,
+        "s" => Face::South,
+        "e" => Face::East,
+        "w" => Face::West,
+        _ => return None,
+    };
+    let mut chars = key.chars();
+    let key = chars.next()?;
+    if chars.next().is_some() {
+        return None;
+    }
+
+    let art = match (*kind, rect) {
+        ("face", []) => LineArt::Base(key),
+        ("decal", []) => LineArt::Decal(Decal { key, u0: 0.0, v0: 0.0, u1: 1.0, v1: 1.0 }),
+        ("decal", [u0, v0, u1, v1]) => {
+            let (u0, v0): (f32, f32) = (u0.parse().ok()?, v0.parse().ok()?);
+            let (u1, v1): (f32, f32) = (u1.parse().ok()?, v1.parse().ok()?);
+            let inside = |lo: f32, hi: f32| 0.0 <= lo && lo < hi && hi <= 1.0;
+            if !inside(u0, u1) || !inside(v0, v1) {
+                return None;
+            }
+            LineArt::Decal(Decal { key, u0, v0, u1, v1 })
+        }
+        _ => return None,
+    };
+    Some(((i, j, face), art))
+}
+
+/// Coordenada u (0..1, izquierda a derecha vista de frente) de un impacto a partir de su `tx` (0..200).
+/// `tx` crece con x o y del mundo; en las caras N y E eso es de derecha a izquierda para quien mira.
+pub fn face_u(tx: usize, face: Face) -> f32 {
+    let u = (tx as f32 / 200.0).clamp(0.0, 1.0);
+    match face {
+        Face::North | Face::East => 1.0 - u,
+        Face::South | Face::West => u,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn only_valid_lines_reach_the_faces() {
+        let text = "\
+# comentario
+face  2 1 n -
+decal 2 1 n P 0.25 0.1 0.75 0.9   # cartel
+decal 2 1 n B
+decal 3 1 s P 0.5 0 0.2 1         # u1 <= u0
+decal 4 1 s P 0 0 1 1.5           # fuera de la cara
+decal 5 1 s P 0 0 uno 1
+decal 6 1 x P
+decal 7 1 s PP
+face  8 1 s - 0 0 1 1
+";
+        let decals = Decals::parse("prueba.decals", text);
+
+        let art = decals.at((2, 1), Face::North).expect("la cara (2, 1, n) tiene arte");
+        assert_eq!(art.base, Some('-'));
+        let rects: Vec<(char, f32, f32, f32, f32)> = art.decals.iter().map(|d| (d.key, d.u0, d.v0, d.u1, d.v1)).collect();
+        assert_eq!(rects, [('P', 0.25, 0.1, 0.75, 0.9), ('B', 0.0, 0.0, 1.0, 1.0)]);
+        assert!(decals.at((2, 1), Face::South).is_none());
+
+        // las líneas inválidas no dejan caras vacías
+        for i in 3..=8 {
+            assert!(decals.at((i, 1), Face::South).is_none(), "celda {}", i);
+        }
+        assert_eq!(decals.faces.len(), 1);
+    }
+}
@@ -1,10 +1,20 @@
 use raylib::prelude::*;
 use crate::player::Player;
 use crate::maze::Maze;
+use crate::sprite::{Sprite, Views};
+
+// Jack: dos cuadros de caminata (claves de assets/textures.txt). Sólo hay dibujos de frente,
+// así que no usa vistas; cuando existan sus vistas de lado y de espaldas, van con `with_views`.
+const JACK_FRAMES: [char; 2] = ['e', 'E'];
+// Pasos que da Jack con cada cuadro de la caminata
+const JACK_STEPS_PER_FRAME: usize = 20;
 
 pub struct Enemy {
     pub pos: Vector2,
     pub frames: Vec<char>,     
+    // opcional: por cada cuadro de `frames`, sus 8 vistas (de frente, de lado, de espaldas...)
+    pub views: Vec<Views>,
+    pub facing: f32, // hacia dónde camina (rad)
     pub current_frame: usize,
     pub step_counter: usize,
     pub steps_per_frame: usize,
@@ -16,17 +26,39 @@ impl Enemy {
         Enemy {
             pos: Vector2::new(x, y),
             frames,
+            views: Vec::new(),
+            facing: 0.0,
             current_frame: 0,
             step_counter: 0,
             steps_per_frame: steps_per_frame.max(1),
         }
     }
 
+    /// Jack en (x, y): camina con dos cuadros.
+    pub fn jack(x: f32, y: f32) -> Self {
+        Enemy::new(x, y, JACK_FRAMES.to_vec(), JACK_STEPS_PER_FRAME)
+    }
+
     /// Devuelve la clave de textura actual
     pub fn current_key(&self) -> char {
         if self.frames.is_empty() { 'e' } else { self.frames[self.current_frame] }
     }
 
+    /// Usa sprites direccionales: `views[i]` son las 8 vistas del cuadro i (mismo número de
+    /// cuadros que `frames`; si falta alguno se repite el último).
+    pub fn with_views(mut self, views: Vec<Views>) -> Self {
+        self.views = views;
+        self
+    }
+
+    /// Billboard con el cuadro actual, para el renderer de sprites.
+    pub fn sprite(&self) -> Sprite {
+        match self.views.get(self.current_frame).or(self.views.last()) {
+            Some(&views) => Sprite::directional(self.pos, views, self.facing),
+            None => Sprite::new(self.pos, self.current_key()),
+        }
+    }
+
     /// Retorna true si se movió (para contar pasos).
     pub fn update(&mut self, player: &Player, maze: &Maze, block_size: usize, speed: f32) -> bool {
         // vector hacia el jugador
@@ -42,6 +74,7 @@ impl Enemy {
         dy = dy / dist * speed;
 
         let mut moved = false;
+        let (start_x, start_y) = (self.pos.x, self.pos.y);
 
         // Intentar mover en X (permite sliding)
         let new_x = self.pos.x + dx;
@@ -59,8 +92,9 @@ impl Enemy {
             moved = true;
         }
 
-        // Si se movió, actualizar animación por pasos
+        // Si se movió, mirar hacia donde avanzó y actualizar animación por pasos
         if moved {
+            self.facing = (self.pos.y - start_y).atan2(self.pos.x - start_x);
             self.step_counter += 1;
             if self.step_counter >= self.steps_per_frame {
                 self.step_counter = 0;
@@ -89,3 +123,30 @@ impl Enemy {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::textures::TextureManager;
+
+    #[test]
+    fn jack_frames_are_in_the_manifest() {
+        let textures = TextureManager::new();
+        for key in JACK_FRAMES {
+            assert!(textures.size(key).is_some(), "el cuadro '{}' de Jack no está en el manifiesto", key);
+        }
+        let jack = Enemy::jack(150.0, 150.0);
+        assert_eq!(jack.sprite().key, 'e');
+        assert_eq!(jack.sprite().views, None);
+    }
+
+    #[test]
+    fn views_follow_the_walk_cycle() {
+        const FIRST: Views = ['a', 'b', 'c', 'd', 'e', 'f', 'g', 'h'];
+        const SECOND: Views = ['A', 'B', 'C', 'D', 'E', 'F', 'G', 'H'];
+        let mut enemy = Enemy::new(150.0, 150.0, vec!['a', 'A'], 1).with_views(vec![FIRST, SECOND]);
+        assert_eq!(enemy.sprite().views, Some(FIRST));
+        enemy.current_frame = 1;
+        assert_eq!(enemy.sprite().views, Some(SECOND));
+    }
+}
@@ -0,0 +1,353 @@
+use raylib::prelude::*;
+
+use crate::framebuffer::Framebuffer;
+
+// Fuente de mapa de bits propia (5x9 por glifo) dibujada directo en el framebuffer,
+// así el texto sale en las capturas y se puede medir. `font_size` se interpreta como en
+// raylib (alto aproximado en px) y se redondea a una escala entera para que no se deforme.
+
+const GLYPH_W: i32 = 5;
+const ADVANCE: i32 = GLYPH_W + 1; // una columna de separación entre letras
+const ACCENT_ROWS: usize = 2; // filas sobre las mayúsculas reservadas para tildes
+const LINE_H: i32 = ACCENT_ROWS as i32 + 9;
+
+/// Alineación horizontal del texto respecto a la x dada.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Align {
+    Left,
+    Center,
+    Right,
+}
+
+#[derive(Clone, Copy)]
+enum Accent {
+    Acute,
+    Grave,
+    Tilde,
+    Diaeresis,
+}
+
+// Letras con tilde: se arman con la letra base y la marca encima.
+const ACCENTED: &[(char, char, Accent)] = &[
+    ('á', 'a', Accent::Acute),
+    ('é', 'e', Accent::Acute),
+    ('í', 'ı', Accent::Acute),
+    ('ó', 'o', Accent::Acute),
+    ('ú', 'u', Accent::Acute),
+    ('à', 'a', Accent::Grave),
+    ('è', 'e', Accent::Grave),
+    ('ì', 'ı', Accent::Grave),
+    ('ò', 'o', Accent::Grave),
+    ('ù', 'u', Accent::Grave),
+    ('ñ', 'n', Accent::Tilde),
+    ('ü', 'u', Accent::Diaeresis),
+    ('ï', 'ı', Accent::Diaeresis),
+    ('Á', 'A', Accent::Acute),
+    ('É', 'E', Accent::Acute),
+    ('Í', 'I', Accent::Acute),
+    ('Ó', 'O', Accent::Acute),
+    ('Ú', 'U', Accent::Acute),
+    ('À', 'A', Accent::Grave),
+    ('È', 'E', Accent::Grave),
+    ('Ì', 'I', Accent::Grave),
+    ('Ò', 'O', Accent::Grave),
+    ('Ù', 'U', Accent::Grave),
+    ('Ñ', 'N', Accent::Tilde),
+    ('Ü', 'U', Accent::Diaeresis),
+];
+
+// Caja para los caracteres que la fuente no tiene.
+const MISSING: [u8; 9] = [0b11111, 0b10001, 0b10001, 0b10001, 0b10001, 0b10001, 0b11111, 0b00000, 0b00000];
+
+// Glifos ASCII 32..=126 (' ' a '~'), 5x9: 7 filas hasta la línea base + 2 de descendentes.
+// Cada fila es una máscara de 5 bits; el bit 4 es la columna izquierda.
+const ASCII: [[u8; 9]; 95] = [
+    [0b00000, 0b00000, 0b00000, 0b00000, 0b00000, 0b00000, 0b00000, 0b00000, 0b00000], // ' '
+    [0b00100, 0b00100, 0b00100, 0b00100, 0b00100, 0b00000, 0b00100, 0b00000, 0b00000], // '!'
+    [0b01010, 0b01010, 0b01010, 0b00000, 0b00000, 0b00000, 0b00000, 0b00000, 0b00000], // '"'
+    [0b01010, 0b01010, 0b11111, 0b01010, 0b11111, 0b01010, 0b01010, 0b00000, 0b00000], // '#'
+    [0b00100, 0b01111, 0b10100, 0b01110, 0b00101, 0b11110, 0b00100, 0b00000, 0b00000], // '$'
+    [0b11000, 0b11001, 0b00010, 0b00100, 0b01000, 0b10011, 0b00011, 0b00000, 0b00000], // '%'
+    [0b01100, 0b10010, 0b10100, 0b01000, 0b10101, 0b10010, 0b01101, 0b00000, 0b00000], // '&'
+    [0b00100, 0b00100, 0b01000, 0b00000, 0b00000, 0b00000, 0b00000, 0b00000, 0b00000], // '\''
+    [0b00010, 0b00100, 0b01000, 0b01000, 0b01000, 0b00100, 0b00010, 0b00000, 0b00000], // '('
+    [0b01000, 0b00100, 0b00010, 0b00010, 0b00010, 0b00100, 0b01000, 0b00000, 0b00000], // ')'
+    [0b00000, 0b00100, 0b10101, 0b01110, 0b10101, 0b00100, 0b00000, 0b00000, 0b00000], // '*'
+    [0b00000, 0b00100, 0b00100, 0b11111, 0b00100, 0b00100, 0b00000, 0b00000, 0b00000], // '+'
+    [0b00000, 0b00000, 0b00000, 0b00000, 0b00000, 0b01100, 0b00100, 0b01000, 0b00000], // ','
+    [0b00000, 0b00000, 0b00000, 0b11111, 0b00000, 0b00000, 0b00000, 0b00000, 0b00000], // '-'
+    [0b00000, 0b00000, 0b00000, 0b00000, 0b00000, 0b01100, 0b01100, 0b00000, 0b00000], // '.'
+    [0b00000, 0b00001, 0b00010, 0b00100, 0b01000, 0b10000, 0b00000, 0b00000, 0b00000], // '/'
+    [0b01110, 0b10001, 0b10011, 0b10101, 0b11001, 0b10001, 0b01110, 0b00000, 0b00000], // '0'
+    [0b00100, 0b01100, 0b00100, 0b00100, 0b00100, 0b00100, 0b01110, 0b00000, 0b00000], // '1'
+    [0b01110, 0b10001, 0b00001, 0b00010, 0b00100, 0b01000, 0b11111, 0b00000, 0b00000], // '2'
+    [0b11111, 0b00010, 0b00100, 0b00010, 0b00001, 0b10001, 0b01110, 0b00000, 0b00000], // '3'
+    [0b00010, 0b00110, 0b01010, 0b10010, 0b11111, 0b00010, 0b00010, 0b00000, 0b00000], // '4'
+    [0b11111, 0b10000, 0b11110, 0b00001, 0b00001, 0b10001, 0b01110, 0b00000, 0b00000], // '5'
+    [0b00110, 0b01000, 0b10000, 0b11110, 0b10001, 0b10001, 0b01110, 0b00000, 0b00000], // '6'
+    [0b11111, 0b00001, 0b00010, 0b00100, 0b01000, 0b01000, 0b01000, 0b00000, 0b00000], // '7'
+    [0b01110, 0b10001, 0b10001, 0b01110, 0b10001, 0b10001, 0b01110, 0b00000, 0b00000], // '8'
+    [0b01110, 0b10001, 0b10001, 0b01111, 0b00001, 0b00010, 0b01100, 0b00000, 0b00000], // '9'
+    [0b00000, 0b01100, 0b01100, 0b00000, 0b01100, 0b01100, 0b00000, 0b00000, 0b00000], // ':'
+    [0b00000, 0b01100, 0b01100, 0b00000, 0b01100, 0b00100, 0b01000, 0b00000, 0b00000], // ';'
+    [0b00010, 0b00100, 0b01000, 0b10000, 0b01000, 0b00100, 0b00010, 0b00000, 0b00000], // '<'
+    [0b00000, 0b00000, 0b11111, 0b00000, 0b11111, 0b00000, 0b00000, 0b00000, 0b00000], // '='
+    [0b01000, 0b00100, 0b00010, 0b00001, 0b00010, 0b00100, 0b01000, 0b00000, 0b00000], // '>'
+    [0b01110, 0b10001, 0b00001, 0b00010, 0b00100, 0b00000, 0b00100, 0b00000, 0b00000], // '?'
+    [0b01110, 0b10001, 0b00001, 0b01101, 0b10101, 0b10101, 0b01110, 0b00000, 0b00000], // '@'
+    [0b01110, 0b10001, 0b10001, 0b10001, 0b11111, 0b10001, 0b10001, 0b00000, 0b00000], // 'A'
+    [0b11110, 0b10001, 0b10001, 0b11110, 0b10001, 0b10001, 0b11110, 0b00000, 0b00000], // 'B'
+    [0b01110, 0b10001, 0b10000, 0b10000, 0b10000, 0b10001, 0b01110, 0b00000, 0b00000], // 'C'
+    [0b11100, 0b10010, 0b10001, 0b10001, 0b10001, 0b10010, 0b11100, 0b00000, 0b00000], // 'D'
+    [0b11111, 0b10000, 0b10000, 0b11110, 0b10000, 0b10000, 0b11111, 0b00000, 0b00000], // 'E'
+    [0b11111, 0b10000, 0b10000, 0b11110, 0b10000, 0b10000, 0b10000, 0b00000, 0b00000], // 'F'
+    [0b01110, 0b10001, 0b10000, 0b10111, 0b10001, 0b10001, 0b01111, 0b00000, 0b00000], // 'G'
+    [0b10001, 0b10001, 0b10001, 0b11111, 0b10001, 0b10001, 0b10001, 0b00000, 0b00000], // 'H'
+    [0b01110, 0b00100, 0b00100, 0b00100, 0b00100, 0b00100, 0b01110, 0b00000, 0b00000], // 'I'
+    [0b00111, 0b00010, 0b00010, 0b00010, 0b00010, 0b10010, 0b01100, 0b00000, 0b00000], // 'J'
+    [0b10001, 0b10010, 0b10100, 0b11000, 0b10100, 0b10010, 0b10001, 0b00000, 0b00000], // 'K'
+    [0b10000, 0b10000, 0b10000, 0b10000, 0b10000, 0b10000, 0b11111, 0b00000, 0b00000], // 'L'
+    [0b10001, 0b11011, 0b10101, 0b10101, 0b10001, 0b10001, 0b10001, 0b00000, 0b00000], // 'M'
+    [0b10001, 0b10001, 0b11001, 0b10101, 0b10011, 0b10001, 0b10001, 0b00000, 0b00000], // 'N'
+    [0b01110, 0b10001, 0b10001, 0b10001, 0b10001, 0b10001, 0b01110, 0b00000, 0b00000], // 'O'
+    [0b11110, 0b10001, 0b10001, 0b11110, 0b10000, 0b10000, 0b10000, 0b00000, 0b00000], // 'P'
+    [0b01110, 0b10001, 0b10001, 0b10001, 0b10101, 0b10010, 0b01101, 0b00000, 0b00000], // 'Q'
+    [0b11110, 0b10001, 0b10001, 0b11110, 0b10100, 0b10010, 0b10001, 0b00000, 0b00000], // 'R'
+    [0b01111, 0b10000, 0b10000, 0b01110, 0b00001, 0b00001, 0b11110, 0b00000, 0b00000], // 'S'
+    [0b11111, 0b00100, 0b00100, 0b00100, 0b00100, 0b00100, 0b00100, 0b00000, 0b00000], // 'T'
+    [0b10001, 0b10001, 0b10001, 0b10001, 0b10001, 0b10001, 0b01110, 0b00000, 0b00000], // 'U'
+    [0b10001, 0b10001, 0b10001, 0b10001, 0b10001, 0b01010, 0b00100, 0b00000, 0b00000], // 'V'
+    [0b10001, 0b10001, 0b10001, 0b10101, 0b10101, 0b10101, 0b01010, 0b00000, 0b00000], // 'W'
+    [0b10001, 0b10001, 0b01010, 0b00100, 0b01010, 0b10001, 0b10001, 0b00000, 0b00000], // 'X'
+    [0b10001, 0b10001, 0b10001, 0b01010, 0b00100, 0b00100, 0b00100, 0b00000, 0b00000], // 'Y'
+    [0b11111, 0b00001, 0b00010, 0b00100, 0b01000, 0b10000, 0b11111, 0b00000, 0b00000], // 'Z'
+    [0b01110, 0b01000, 0b01000, 0b01000, 0b01000, 0b01000, 0b01110, 0b00000, 0b00000], // '['
+    [0b00000, 0b10000, 0b01000, 0b00100, 0b00010, 0b00001, 0b00000, 0b00000, 0b00000], // '\\'
+    [0b01110, 0b00010, 0b00010, 0b00010, 0b00010, 0b00010, 0b01110, 0b00000, 0b00000], // ']'
+    [0b00100, 0b01010, 0b10001, 0b00000, 0b00000, 0b00000, 0b00000, 0b00000, 0b00000], // '^'
+    [0b00000, 0b00000, 0b00000, 0b00000, 0b00000, 0b00000, 0b11111, 0b00000, 0b00000], // '_'
+    [0b01000, 0b00100, 0b00000, 0b00000, 0b00000, 0b00000, 0b00000, 0b00000, 0b00000], // '`'
+    [0b00000, 0b00000, 0b01110, 0b00001, 0b01111, 0b10001, 0b01111, 0b00000, 0b00000], // 'a'
+    [0b10000, 0b10000, 0b10110, 0b11001, 0b10001, 0b10001, 0b11110, 0b00000, 0b00000], // 'b'
+    [0b00000, 0b00000, 0b01110, 0b10000, 0b10000, 0b10001, 0b01110, 0b00000, 0b00000], // 'c'
+    [0b00001, 0b00001, 0b01101, 0b10011, 0b10001, 0b10001, 0b01111, 0b00000, 0b00000], // 'd'
+    [0b00000, 0b00000, 0b01110, 0b10001, 0b11111, 0b10000, 0b01110, 0b00000, 0b00000], // 'e'
+    [0b00110, 0b01001, 0b01000, 0b11100, 0b01000, 0b01000, 0b01000, 0b00000, 0b00000], // 'f'
+    [0b00000, 0b00000, 0b01111, 0b10001, 0b10001, 0b10001, 0b01111, 0b00001, 0b01110], // 'g'
+    [0b10000, 0b10000, 0b10110, 0b11001, 0b10001, 0b10001, 0b10001, 0b00000, 0b00000], // 'h'
+    [0b00100, 0b00000, 0b01100, 0b00100, 0b00100, 0b00100, 0b01110, 0b00000, 0b00000], // 'i'
+    [0b00010, 0b00000, 0b00110, 0b00010, 0b00010, 0b00010, 0b00010, 0b10010, 0b01100], // 'j'
+    [0b10000, 0b10000, 0b10010, 0b10100, 0b11000, 0b10100, 0b10010, 0b00000, 0b00000], // 'k'
+    [0b01100, 0b00100, 0b00100, 0b00100, 0b00100, 0b00100, 0b01110, 0b00000, 0b00000], // 'l'
+    [0b00000, 0b00000, 0b11010, 0b10101, 0b10101, 0b10101, 0b10101, 0b00000, 0b00000], // 'm'
+    [0b00000, 0b00000, 0b10110, 0b11001, 0b10001, 0b10001, 0b10001, 0b00000, 0b00000], // 'n'
+    [0b00000, 0b00000, 0b01110, 0b10001, 0b10001, 0b10001, 0b01110, 0b00000, 0b00000], // 'o'
+    [0b00000, 0b00000, 0b11110, 0b10001, 0b10001, 0b10001, 0b11110, 0b10000, 0b10000], // 'p'
+    [0b00000, 0b00000, 0b01111, 0b10001, 0b10001, 0b10001, 0b01111, 0b00001, 0b00001], // 'q'
+    [0b00000, 0b00000, 0b10110, 0b11001, 0b10000, 0b10000, 0b10000, 0b00000, 0b00000], // 'r'
+    [0b00000, 0b00000, 0b01111, 0b10000, 0b01110, 0b00001, 0b11110, 0b00000, 0b00000], // 's'
+    [0b01000, 0b01000, 0b11100, 0b01000, 0b01000, 0b01001, 0b00110, 0b00000, 0b00000], // 't'
+    [0b00000, 0b00000, 0b10001, 0b10001, 0b10001, 0b10011, 0b01101, 0b00000, 0b00000], // 'u'
+    [0b00000, 0b00000, 0b10001, 0b10001, 0b10001, 0b01010, 0b00100, 0b00000, 0b00000], // 'v'
+    [0b00000, 0b00000, 0b10001, 0b10001, 0b10101, 0b10101, 0b01010, 0b00000, 0b00000], // 'w'
+    [0b00000, 0b00000, 0b10001, 0b01010, 0b00100, 0b01010, 0b10001, 0b00000, 0b00000], // 'x'
+    [0b00000, 0b00000, 0b10001, 0b10001, 0b10001, 0b10001, 0b01111, 0b00001, 0b01110], // 'y'
+    [0b00000, 0b00000, 0b11111, 0b00010, 0b00100, 0b01000, 0b11111, 0b00000, 0b00000], // 'z'
+    [0b00010, 0b00100, 0b00100, 0b01000, 0b00100, 0b00100, 0b00010, 0b00000, 0b00000], // '{'
+    [0b00100, 0b00100, 0b00100, 0b00100, 0b00100, 0b00100, 0b00100, 0b00000, 0b00000], // '|'
+    [0b01000, 0b00100, 0b00100, 0b00010, 0b00100, 0b00100, 0b01000, 0b00000, 0b00000], // '}'
+    [0b00000, 0b00000, 0b01000, 0b10101, 0b00010, 0b00000, 0b00000, 0b00000, 0b00000], // '~'
+];
+
+// Glifos fuera de ASCII dibujados aparte.
+const EXTRA: &[(char, [u8; 9])] = &[
+    ('¡', [0b00100, 0b00000, 0b00100, 0b00100, 0b00100, 0b00100, 0b00100, 0b00000, 0b00000]),
+    ('¿', [0b00100, 0b00000, 0b00100, 0b01000, 0b10000, 0b10001, 0b01110, 0b00000, 0b00000]),
+    ('ı', [0b00000, 0b00000, 0b01100, 0b00100, 0b00100, 0b00100, 0b01110, 0b00000, 0b00000]),
+    ('°', [0b01100, 0b10010, 0b01100, 0b00000, 0b00000, 0b00000, 0b00000, 0b00000, 0b00000]),
+];
+
+fn accent_rows(accent: Accent) -> [u8; 2] {
+    match accent {
+        Accent::Acute => [0b00010, 0b00100],
+        Accent::Grave => [0b01000, 0b00100],
+        Accent::Tilde => [0b01101, 0b10110],
+        Accent::Diaeresis => [0b01010, 0b00000],
+    }
+}
+
+fn base_glyph(ch: char) -> Option<[u8; 9]> {
+    match ch as u32 {
+        32..=126 => Some(ASCII[ch as usize - 32]),
+        _ => EXTRA.iter().find(|(c, _)| *c == ch).map(|(_, rows)| *rows),
+    }
+}
+
+/// Filas del glifo de `ch`: 2 de margen (tildes de mayúsculas) + 9 del glifo.
+fn glyph(ch: char) -> [u8; LINE_H as usize] {
+    let mut rows = [0u8; LINE_H as usize];
+    if let Some(base) = base_glyph(ch) {
+        rows[ACCENT_ROWS..].copy_from_slice(&base);
+    } else if let Some(&(_, base, accent)) = ACCENTED.iter().find(|(c, _, _)| *c == ch) {
+        rows[ACCENT_ROWS..].copy_from_slice(&base_glyph(base).unwrap_or(MISSING));
+        // minúsculas: la tilde va en las filas libres sobre la x; mayúsculas: en el margen
+        let at = if base.is_uppercase() { 0 } else { ACCENT_ROWS };
+        rows[at..at + 2].copy_from_slice(&accent_rows(accent));
+    } else {
+        rows[ACCENT_ROWS..].copy_from_slice(&MISSING);
+    }
+    rows
+}
+
+/// Escala entera (px por punto del glifo) que corresponde a `font_size`.
+pub fn scale_for(font_size: i32) -> i32 {
+    ((font_size + LINE_H / 2) / LINE_H).max(1)
+}
+
+/// Alto en px de una línea de texto.
+pub fn line_height(font_size: i32) -> i32 {
+    LINE_H * scale_for(font_size)
+}
+
+/// Ancho en px de una sola línea (sin saltos).
+fn line_width(line: &str, font_size: i32) -> i32 {
+    let chars = line.chars().count() as i32;
+    if chars == 0 { 0 } else { (chars * ADVANCE - 1) * scale_for(font_size) }
+}
+
+/// Tamaño (ancho, alto) en px del texto; los '\n' empiezan líneas nuevas.
+pub fn measure_text(text: &str, font_size: i32) -> (i32, i32) {
+    let width = text.split('\n').map(|line| line_width(line, font_size)).max().unwrap_or(0);
+    let lines = text.split('\n').count() as i32;
+    (width, lines * line_height(font_size))
+}
+
+/// Parte el texto en líneas de como mucho `max_width` px, cortando entre palabras
+/// (una palabra que no cabe sola se corta por letras). Respeta los '\n'.
+pub fn wrap_text(text: &str, font_size: i32, max_width: i32) -> Vec<String> {
+    let max_chars = ((max_width / scale_for(font_size) + 1) / ADVANCE).max(1) as usize;
+    let mut lines = Vec::new();
+
+    for paragraph in text.split('\n') {
+        let mut line = String::new();
+        let mut line_len = 0;
+        for word in paragraph.split_whitespace() {
+            let mut word: Vec<char> = word.chars().collect();
+            // palabra más larga que una línea: se corta en trozos
+            while word.len() > max_chars {
+                if line_len > 0 {
+                    lines.push(std::mem::take(&mut line));
+                    line_len = 0;
+                }
+                lines.push(word.drain(..max_chars).collect());
+            }
+            if word.is_empty() {
+                continue;
+            }
+            let needed = if line_len == 0 { word.len() } else { line_len + 1 + word.len() };
+            if needed > max_chars {
+                lines.push(std::mem::take(&mut line));
+                line_len = 0;
+            }
+            if line_len > 0 {
+                line.push(' ');
+                line_len += 1;
+            }
+            line.extend(word.iter());
+            line_len += word.len();
+        }
+        lines.push(line);
+    }
+    lines
+}
+
+/// Dibuja `text` con la esquina superior en `y`; `x` es el borde izquierdo, el centro o el borde
+/// derecho según `align` (por línea). Con alpha < 255 el texto se mezcla con lo que hay debajo.
+pub fn draw_text(framebuffer: &mut Framebuffer, text: &str, x: i32, y: i32, font_size: i32, color: Color, align: Align) {
+    let scale = scale_for(font_size);
+    for (n, line) in text.split('\n').enumerate() {
+        let width = line_width(line, font_size);
+        let left = match align {
+            Align::Left => x,
+            Align::Center => x - width / 2,
+            Align::Right => x - width,
+        };
+        let top = y + n as i32 * LINE_H * scale;
+        for (i, ch) in line.chars().enumerate() {
+            if ch == ' ' {
+                continue;
+            }
+            let gx = left + i as i32 * ADVANCE * scale;
+            for (row, bits) in glyph(ch).iter().enumerate() {
+                for col in 0..GLYPH_W {
+                    if bits & (1 << (GLYPH_W - 1 - col)) == 0 {
+                        continue;
+                    }
+                    let (px, py) = (gx + col * scale, top + row as i32 * scale);
+                    if color.a == 255 {
+                        framebuffer.fill_rect(px, py, scale, scale, color);
+                    } else {
+                        framebuffer.blend_rect(px, py, scale, scale, color);
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Como `draw_text`, pero partiendo el texto a `max_width` px. Devuelve el alto usado.
+pub fn draw_text_wrapped(
+    framebuffer: &mut Framebuffer,
+    text: &str,
+    x: i32,
+    y: i32,
+    max_width: i32,
+    font_size: i32,
+    color: Color,
+    align: Align,
+) -> i32 {
+    let lines = wrap_text(text, font_size, max_width);
+    draw_text(framebuffer, &lines.join("\n"), x, y, font_size, color, align);
+    lines.len() as i32 * line_height(font_size)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn measure_takes_the_widest_line_and_counts_lines() {
+        // a 11 px cada punto del glifo es un px: 6 px por letra, sin la separación final
+        assert_eq!(scale_for(11), 1);
+        assert_eq!(measure_text("ab\ncde", 11), (17, 22));
+        assert_eq!(measure_text("ab\ncde", 22), (34, 44));
+        assert_eq!(measure_text("a\n\nb", 11), (5, 33));
+        assert_eq!(measure_text("", 11), (0, 11));
+    }
+
+    #[test]
+    fn wrap_cuts_long_words_and_keeps_newlines() {
+        // 29 px: caben 5 letras
+        let lines = wrap_text("ab abcdefghijkl cd", 11, 29);
+        assert_eq!(lines, ["ab", "abcde", "fghij", "kl cd"]);
+        assert!(lines.iter().all(|l| measure_text(l, 11).0 <= 29));
+
+        assert_eq!(wrap_text("hola\nmundo cruel", 11, 200), ["hola", "mundo cruel"]);
+        assert_eq!(wrap_text("a\n\nb", 11, 200), ["a", "", "b"]);
+        assert_eq!(wrap_text("hola mundo\ncruel", 11, 29), ["hola", "mundo", "cruel"]);
+    }
+
+    #[test]
+    fn spanish_text_has_glyphs() {
+        for ch in "¡Escapaste! ¿Qué? áéíóúñü ÁÉÍÓÚÑÜ".chars() {
+            assert_ne!(glyph(ch)[ACCENT_ROWS..], MISSING, "'{}' sin glifo", ch);
+        }
+        // las mayúsculas llevan la tilde en el margen, sobre la letra sin tocar
+        for &(ch, base, accent) in ACCENTED.iter().filter(|(c, _, _)| c.is_uppercase()) {
+            let rows = glyph(ch);
+            assert_eq!(rows[..ACCENT_ROWS], accent_rows(accent), "'{}'", ch);
+            assert_eq!(rows[ACCENT_ROWS..], glyph(base)[ACCENT_ROWS..], "'{}'", ch);
+        }
+        assert_eq!(glyph('€')[ACCENT_ROWS..], MISSING);
+    }
+}
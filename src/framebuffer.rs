@@ -1,52 +1,48 @@
 use raylib::prelude::*;
+use raylib::ffi;
+use std::ffi::CString;
+use crate::font::{self, Align};
+use crate::postfx::PostFx;
+use crate::present::Presenter;
 
 pub struct Framebuffer {
     pub width: i32,
     pub height: i32,
-    pub color_buffer: Image,
     background_color: Color,
     current_color: Color,
+    // única fuente de verdad de los píxeles (fila por fila); se sube a la GPU en swap_buffers
     pixel_data: Vec<Color>,
-    overlays: Vec<(String, i32, i32, i32, Color)>,
-    reuse_texture: Option<Texture2D>,
-
-    circle_overlays: Vec<(i32, i32, f32, f32)>,
-    mask_texture: Option<Texture2D>,
-    health_to_draw: Option<(i32, i32)>, // (current_lives, max_lives)
-
+    // los mismos píxeles ordenados por columnas, para la vista 3D; se guarda entre frames
+    columns: Vec<Color>,
 }
 
 impl Framebuffer {
     pub fn new(width: i32, height: i32, background_color: Color) -> Self {
         let size = (width * height) as usize;
         let pixel_data = vec![background_color; size];
-        let color_buffer = Image::gen_image_color(width, height, background_color);
         Framebuffer {
             width,
             height,
-            color_buffer,
             background_color,
             current_color: Color::WHITE,
             pixel_data,
-            overlays: Vec::new(),
-            reuse_texture: None,
-            circle_overlays: Vec::new(),
-            mask_texture: None,
-            health_to_draw:None
+            columns: Vec::new(),
         }
     }
 
     pub fn clear(&mut self) {
         self.pixel_data.fill(self.background_color);
-        self.color_buffer =
-            Image::gen_image_color(self.width, self.height, self.background_color)
+    }
+
+    /// Deja todo transparente, para usar el frame como capa encima de otra imagen.
+    pub fn clear_transparent(&mut self) {
+        self.pixel_data.fill(Color::new(0, 0, 0, 0));
     }
 
     pub fn set_pixel(&mut self, x: i32, y: i32) {
         if x >= 0 && x < self.width && y >= 0 && y < self.height {
             let index = (y * self.width + x) as usize;
             self.pixel_data[index] = self.current_color;
-            Image::draw_pixel(&mut self.color_buffer, x as i32, y as i32, self.current_color);
         }
     }
 
@@ -59,214 +55,352 @@ impl Framebuffer {
         self.current_color = color;
     }
 
-    pub fn render_to_file(&self, file_path: &str) {
-        Image::export_image(&self.color_buffer, file_path);
+    /// Guarda el frame como imagen (el formato sale de la extensión). Devuelve si se pudo escribir.
+    pub fn render_to_file(&self, file_path: &str) -> bool {
+        let Ok(c_path) = CString::new(file_path) else {
+            return false;
+        };
+        let image = self.to_image();
+        // Image::export_image no avisa si falla; ExportImage sí
+        unsafe { ffi::ExportImage(*image, c_path.as_ptr()) }
+    }
+
+    /// Crea una Image (RGBA8) con el contenido actual del framebuffer.
+    fn to_image(&self) -> Image {
+        let image = Image::gen_image_color(self.width, self.height, self.background_color);
+        let bytes = self.pixel_bytes();
+        unsafe {
+            std::ptr::copy_nonoverlapping(bytes.as_ptr(), image.data as *mut u8, bytes.len());
+        }
+        image
     }
 
-    /// Añade un overlay de texto que se dibujará en el próximo `swap_buffers`.
+
+    /// Dibuja texto en los píxeles con la fuente de mapa de bits (esquina superior izquierda en x, y).
     /// Parámetros: texto, x, y, font_size, color
     pub fn draw_text(&mut self, text: &str, x: i32, y: i32, font_size: i32, color: Color) {
-        self.overlays.push((text.to_string(), x, y, font_size, color));
+        font::draw_text(self, text, x, y, font_size, color, Align::Left);
     }
 
-    /// Añade un overlay de "vignette" circular: fuera del radio estará oscuro.
-    /// center in screen coords (px), max_radius en px, darkness en [0.0..1.0].
-    /// Llamar antes de swap_buffers cada frame (se limpia después de dibujar).
-    pub fn draw_vignette(&mut self, center_x: i32, center_y: i32, max_radius: f32, darkness: f32) {
-        self.circle_overlays.push((center_x, center_y, max_radius, darkness.max(0.0).min(1.0)));
+    /// Como `draw_text`, con `x` como borde izquierdo, centro o borde derecho según `align`.
+    pub fn draw_text_aligned(&mut self, text: &str, x: i32, y: i32, font_size: i32, color: Color, align: Align) {
+        font::draw_text(self, text, x, y, font_size, color, align);
     }
 
-pub fn swap_buffers(&mut self, window: &mut RaylibHandle, raylib_thread: &RaylibThread) {
-    // 1) actualizar o crear la textura que contiene el framebuffer
-    if let Some(texture) = &mut self.reuse_texture {
-        let byte_len = (self.width * self.height * 4) as usize;
-        let pixels: &[u8] = unsafe {
-            std::slice::from_raw_parts(self.color_buffer.data as *const u8, byte_len)
-        };
-        texture.update_texture(pixels).unwrap();
-    } else {
-        let texture = window
-            .load_texture_from_image(raylib_thread, &self.color_buffer)
-            .expect("Failed to create reuse_texture");
-        self.reuse_texture = Some(texture);
+    /// Texto partido en líneas de como mucho `max_width` px; devuelve el alto usado.
+    pub fn draw_text_wrapped(&mut self, text: &str, x: i32, y: i32, max_width: i32, font_size: i32, color: Color, align: Align) -> i32 {
+        font::draw_text_wrapped(self, text, x, y, max_width, font_size, color, align)
     }
 
-    // 2) si hay overlays de máscara, aseguramos la mask_texture ANTES de entrar a begin_drawing
-    if !self.circle_overlays.is_empty() {
-        self.ensure_mask_texture(window, raylib_thread);
+    /// Entrega el frame terminado al presentador (ventana, imagen, terminal...).
+    pub fn swap_buffers(&mut self, presenter: &mut dyn Presenter) {
+        presenter.present(self);
     }
 
-    // 3) comenzar a dibujar
-    if let Some(texture) = &self.reuse_texture {
-        let mut d = window.begin_drawing(raylib_thread);
+    /// Como `swap_buffers`, pero el frame es una capa (HUD) encima de `scene`, que puede tener
+    /// otra resolución y se estira al tamaño del frame al presentarla.
+    pub fn swap_buffers_over(&mut self, scene: &Framebuffer, presenter: &mut dyn Presenter) {
+        presenter.present_layers(scene, self);
+    }
 
-        // dibujar framebuffer (pantalla principal)
-        d.draw_texture(texture, 0, 0, Color::WHITE);
+    /// Los píxeles como bytes RGBA8 (Color es #[repr(C)] de 4 u8), listos para subir a la GPU.
+    pub fn pixel_bytes(&self) -> &[u8] {
+        unsafe {
+            std::slice::from_raw_parts(self.pixel_data.as_ptr() as *const u8, self.pixel_data.len() * 4)
+        }
+    }
 
-        // dibujar overlays de texto
-        for (text, x, y, font_size, color) in &self.overlays {
-            d.draw_text(text, *x, *y, *font_size, *color);
+    pub fn get_pixel_color(&self, x: i32, y: i32) -> Option<Color> {
+        if x >= 0 && x < self.width && y >= 0 && y < self.height {
+            let index = (y * self.width + x) as usize;
+            Some(self.pixel_data[index])
+        } else {
+            None
         }
+    }
 
-        // 4) dibujar máscaras (linterna / oscuridad) usando la mask_texture escalada y recortada
-        if let Some(mask_tex) = &self.mask_texture {
-            // tamaño de pantalla (dest full-screen)
-            let dest_w = self.width as f32;
-            let dest_h = self.height as f32;
-
-            // Tamaño original de la textura de máscara (asumimos cuadrada)
-            let mask_w = mask_tex.width() as f32;
-            let mask_h = mask_tex.height() as f32;
-
-            // FRACTION que define el "radio claro de referencia" dentro de la máscara.
-            // Si quieres que el hueco pueda llegar a ser muy pequeño en pantalla,
-            // reduce CLEAR_FRAC (ej. 0.05). Si quieres hueco más grande, aumenta.
-            const CLEAR_FRAC: f32 = 0.08; // 0.05..0.15 es rango razonable
-
-            for (cx, cy, max_r, darkness) in &self.circle_overlays {
-                // si darkness == 0 no aplicamos
-                if *darkness <= 0.0 {
-                    continue;
-                }
+    /// Píxeles del frame, fila por fila.
+    pub fn pixels(&self) -> &[Color] {
+        &self.pixel_data
+    }
 
-                // desired_radius = radio en píxeles que queremos que tenga el hueco claro en pantalla
-                let desired_radius = (*max_r).max(1.0);
+    /// Buffer ordenado por columnas del tamaño del frame: la columna x ocupa
+    /// `[x * height .. (x + 1) * height]`, así cada columna es un trozo contiguo.
+    /// Se reserva una vez y trae lo del frame anterior: quien lo use lo escribe entero.
+    pub fn columns_mut(&mut self) -> &mut [Color] {
+        self.columns.resize(self.pixel_data.len(), self.background_color);
+        &mut self.columns
+    }
 
-                // radio de referencia en la máscara (en píxeles): 
-                // toma la mitad de la menor dimensión y multiplícalo por CLEAR_FRAC
-                let mask_half = (mask_w.min(mask_h)) / 2.0;
-                let ref_radius_in_mask = mask_half * CLEAR_FRAC;
+    /// Pasa el buffer por columnas (ver `columns_mut`) al frame: la única transposición.
+    pub fn commit_columns(&mut self) {
+        let (w, h) = (self.width as usize, self.height as usize);
+        if self.columns.len() != w * h {
+            return;
+        }
+        for (y, row) in self.pixel_data.chunks_mut(w).enumerate() {
+            for (x, pixel) in row.iter_mut().enumerate() {
+                *pixel = self.columns[x * h + y];
+            }
+        }
+    }
 
-                // fórmula: desired_radius = ref_radius_in_mask * (dest_w / src_w)
-                // => src_w = ref_radius_in_mask * dest_w / desired_radius
-                //
-                // NOTA: con CLEAR_FRAC pequeño, src_w será razonable y no siempre se clampa.
-                let mut src_w = (ref_radius_in_mask * dest_w) / desired_radius;
+    /// `overlay` encima de `scene` estirada a su tamaño, en un frame nuevo: lo que muestra
+    /// `swap_buffers_over`, armado en CPU (para capturas y presentadores sin GPU).
+    pub fn composed(scene: &Framebuffer, overlay: &Framebuffer) -> Framebuffer {
+        let mut frame = Framebuffer::new(overlay.width, overlay.height, overlay.background_color);
+        frame.blit_scaled(scene);
+        for (pixel, &top) in frame.pixel_data.iter_mut().zip(&overlay.pixel_data) {
+            *pixel = blend_over(*pixel, top);
+        }
+        frame
+    }
 
-                // clamp src_w a rango util
-                if src_w < 1.0 { src_w = 1.0; }
-                if src_w > mask_w { src_w = mask_w; }
+    /// Copia `src` (p.ej. la vista 3D a resolución interna) escalado en CPU con vecino más cercano
+    /// a todo el framebuffer.
+    pub fn blit_scaled(&mut self, src: &Framebuffer) {
+        if src.width <= 0 || src.height <= 0 {
+            return;
+        }
+        let src_x: Vec<usize> = (0..self.width)
+            .map(|x| (x as i64 * src.width as i64 / self.width as i64) as usize)
+            .collect();
+
+        for y in 0..self.height {
+            let sy = (y as i64 * src.height as i64 / self.height as i64) as usize;
+            let row = sy * src.width as usize;
+            let dst_row = (y * self.width) as usize;
+            for x in 0..self.width as usize {
+                self.pixel_data[dst_row + x] = src.pixel_data[row + src_x[x]];
+            }
+        }
+    }
 
-                // Calculamos rect fuente centrado en la textura de máscara
-                let src_cx = mask_w / 2.0;
-                let src_cy = mask_h / 2.0;
-                let src_x = (src_cx - src_w / 2.0).max(0.0);
-                let src_y = (src_cy - src_w / 2.0).max(0.0);
+    /// Pasa el frame por la cadena de post-proceso (`time` en s anima el ruido y el temblor).
+    pub fn apply_effects(&mut self, effects: &PostFx, time: f32) {
+        effects.apply(&mut self.pixel_data, self.width as usize, self.height as usize, time);
+    }
 
-                let src = Rectangle::new(src_x, src_y, src_w, src_w);
+    // --- Primitivas de dibujo: reciben el color directamente y recortan a los bordes ---
 
-                // Dibujamos la máscara ocupando toda la pantalla, pero desplazada para que
-                // el punto (cx,cy) del hueco quede en la posición correcta relativa
-                // dentro del dest (esto permite centrar la linterna en pantalla o sobre el enemigo).
-                let dest_x = (*cx as f32) - (dest_w / 2.0);
-                let dest_y = (*cy as f32) - (dest_h / 2.0);
-                let dest = Rectangle::new(dest_x, dest_y, dest_w, dest_h);
+    /// Pinta un píxel de `color` tal cual (sin mezclar).
+    pub fn put_pixel(&mut self, x: i32, y: i32, color: Color) {
+        if x >= 0 && x < self.width && y >= 0 && y < self.height {
+            let index = (y * self.width + x) as usize;
+            self.pixel_data[index] = color;
+        }
+    }
 
-                let origin = Vector2::new(0.0, 0.0);
+    /// Mezcla `color` sobre el píxel según su alpha.
+    pub fn blend_pixel(&mut self, x: i32, y: i32, color: Color) {
+        if x >= 0 && x < self.width && y >= 0 && y < self.height {
+            let index = (y * self.width + x) as usize;
+            self.pixel_data[index] = blend_over(self.pixel_data[index], color);
+        }
+    }
 
-                // tint: modulamos alpha por darkness (0..1)
-                let tint_alpha = (darkness.clamp(0.0, 1.0) * 255.0).round() as u8;
-                let tint = Color::new(255, 255, 255, tint_alpha);
+    /// Rectángulo relleno (sin mezclar).
+    pub fn fill_rect(&mut self, x: i32, y: i32, w: i32, h: i32, color: Color) {
+        let Some((x0, y0, x1, y1)) = self.clip_rect(x, y, w, h) else {
+            return;
+        };
+        let stride = self.width as usize;
+        for row in y0..y1 {
+            self.pixel_data[row * stride + x0..row * stride + x1].fill(color);
+        }
+    }
 
-                // draw call rápido (GPU)
-                d.draw_texture_pro(mask_tex, src, dest, origin, 0.0, tint);
+    /// Rectángulo relleno mezclado con el fondo según el alpha de `color`.
+    pub fn blend_rect(&mut self, x: i32, y: i32, w: i32, h: i32, color: Color) {
+        let Some((x0, y0, x1, y1)) = self.clip_rect(x, y, w, h) else {
+            return;
+        };
+        let stride = self.width as usize;
+        for row in y0..y1 {
+            for p in &mut self.pixel_data[row * stride + x0..row * stride + x1] {
+                *p = blend_over(*p, color);
             }
         }
+    }
 
-        // --- dibujar barras de vida ---
-        if let Some((current_lives, max_lives)) = self.health_to_draw {
-            // parámetros visuales
-            let pad_left = 10_i32;
-            let pad_bottom = 10_i32;
-            let bar_w = 140_i32;
-            let bar_h = 18_i32;
-            let spacing = 8_i32;
-
-            // dibujamos barras apiladas verticalmente en la esquina inferior izquierda
-            for i in 0..max_lives {
-                // i=0 -> barra inferior, i=1 -> barra encima, etc.
-                let idx = i as i32;
-                let x = pad_left;
-                let y = (self.height - pad_bottom) - ((idx + 1) * (bar_h + spacing));
-
-                // fondo de la barra (gris)
-                d.draw_rectangle(x, y, bar_w, bar_h, Color::new(60, 60, 60, 200));
-
-                // si la barra está "viva", dibujar en verde (o parcialmente rellenada)
-                if i < current_lives {
-                    d.draw_rectangle(x + 3, y + 3, bar_w - 6, bar_h - 6, Color::new(40, 200, 40, 255));
-                } else {
-                    // barra vacía: un tono más oscuro/rojo tenue
-                    d.draw_rectangle(x + 3, y + 3, bar_w - 6, bar_h - 6, Color::new(120, 0, 0, 200));
-                }
-            }
+    /// Contorno de 1 px de un rectángulo.
+    pub fn draw_rect(&mut self, x: i32, y: i32, w: i32, h: i32, color: Color) {
+        if w <= 0 || h <= 0 {
+            return;
         }
+        self.fill_rect(x, y, w, 1, color);
+        self.fill_rect(x, y + h - 1, w, 1, color);
+        self.fill_rect(x, y, 1, h, color);
+        self.fill_rect(x + w - 1, y, 1, h, color);
     }
 
-    // 5) limpiar overlays ya dibujados
-    self.overlays.clear();
-    self.circle_overlays.clear();
-    self.health_to_draw = None;
-}
+    /// Línea de Bresenham de (x0, y0) a (x1, y1), ambos extremos incluidos.
+    pub fn draw_line(&mut self, x0: i32, y0: i32, x1: i32, y1: i32, color: Color) {
+        let dx = (x1 - x0).abs();
+        let dy = -(y1 - y0).abs();
+        let sx = if x0 < x1 { 1 } else { -1 };
+        let sy = if y0 < y1 { 1 } else { -1 };
+        let (mut x, mut y) = (x0, y0);
+        let mut err = dx + dy;
+        loop {
+            self.put_pixel(x, y, color);
+            if x == x1 && y == y1 {
+                break;
+            }
+            let e2 = 2 * err;
+            if e2 >= dy {
+                err += dy;
+                x += sx;
+            }
+            if e2 <= dx {
+                err += dx;
+                y += sy;
+            }
+        }
+    }
 
+    /// Circunferencia de radio `r` (algoritmo del punto medio).
+    pub fn draw_circle(&mut self, cx: i32, cy: i32, r: i32, color: Color) {
+        if r < 0 {
+            return;
+        }
+        let (mut x, mut y) = (r, 0);
+        let mut err = 1 - r;
+        while x >= y {
+            for (px, py) in [(x, y), (y, x), (-y, x), (-x, y), (-x, -y), (-y, -x), (y, -x), (x, -y)] {
+                self.put_pixel(cx + px, cy + py, color);
+            }
+            y += 1;
+            if err < 0 {
+                err += 2 * y + 1;
+            } else {
+                x -= 1;
+                err += 2 * (y - x) + 1;
+            }
+        }
+    }
 
+    /// Círculo relleno de radio `r` (por tramos horizontales).
+    pub fn fill_circle(&mut self, cx: i32, cy: i32, r: i32, color: Color) {
+        if r < 0 {
+            return;
+        }
+        for dy in -r..=r {
+            let half = ((r * r - dy * dy) as f32).sqrt() as i32;
+            self.fill_rect(cx - half, cy + dy, half * 2 + 1, 1, color);
+        }
+    }
 
-    pub fn get_pixel_color(&self, x: i32, y: i32) -> Option<Color> {
-        if x >= 0 && x < self.width && y >= 0 && y < self.height {
-            let index = (y * self.width + x) as usize;
-            Some(self.pixel_data[index])
-        } else {
-            None
+    /// Copia la región (sx, sy, w, h) de la imagen `src` (de `src_w` x `src_h`) en (x, y).
+    /// Recorta contra ambos bordes; los texeles con alpha 0 no se copian y los semitransparentes se mezclan.
+    pub fn blit_region(
+        &mut self,
+        x: i32,
+        y: i32,
+        src: &[Color],
+        src_w: i32,
+        src_h: i32,
+        sx: i32,
+        sy: i32,
+        w: i32,
+        h: i32,
+    ) {
+        // recortar la región a la imagen de origen...
+        let (sx0, sy0) = (sx.max(0), sy.max(0));
+        let (sx1, sy1) = ((sx + w).min(src_w), (sy + h).min(src_h));
+        // ...y el destino al framebuffer
+        let (dx0, dy0) = ((x + sx0 - sx).max(0), (y + sy0 - sy).max(0));
+        let (dx1, dy1) = ((x + sx1 - sx).min(self.width), (y + sy1 - sy).min(self.height));
+
+        for dy in dy0..dy1 {
+            let row = ((dy - y + sy) * src_w) as usize;
+            for dx in dx0..dx1 {
+                let color = src[row + (dx - x + sx) as usize];
+                match color.a {
+                    0 => {}
+                    255 => self.pixel_data[(dy * self.width + dx) as usize] = color,
+                    _ => self.blend_pixel(dx, dy, color),
+                }
+            }
         }
     }
 
-        /// Asegura que exista mask_texture; si no, la crea a partir de una Image radial.
-    /// mask_size: tamaño cuadrado de la máscara en px (p.ej. 512)
-    fn ensure_mask_texture(&mut self, window: &mut RaylibHandle, raylib_thread: &RaylibThread) {
-        if self.mask_texture.is_some() {
-            return;
+    /// Recorta un rectángulo al framebuffer: (x0, y0, x1, y1) con x1/y1 exclusivos, o None si queda vacío.
+    fn clip_rect(&self, x: i32, y: i32, w: i32, h: i32) -> Option<(usize, usize, usize, usize)> {
+        let (x0, y0) = (x.max(0), y.max(0));
+        let (x1, y1) = (x.saturating_add(w).min(self.width), y.saturating_add(h).min(self.height));
+        if x0 >= x1 || y0 >= y1 {
+            return None;
         }
+        Some((x0 as usize, y0 as usize, x1 as usize, y1 as usize))
+    }
+}
+
+/// Mezcla `top` sobre `base` según el alpha de `top`. Sobre un `base` opaco queda opaco; sobre uno
+/// (semi)transparente el alpha se acumula, así una capa con partes transparentes (el HUD encima de
+/// la escena) se puede mezclar después con lo de abajo y da lo mismo que dibujarla encima.
+pub fn blend_over(base: Color, top: Color) -> Color {
+    let a = top.a as u32;
+    if base.a == 255 {
+        let mix = |b: u8, t: u8| ((t as u32 * a + b as u32 * (255 - a)) / 255) as u8;
+        return Color::new(mix(base.r, top.r), mix(base.g, top.g), mix(base.b, top.b), 255);
+    }
+    // lo que se ve de `base` a través de `top`
+    let below = base.a as u32 * (255 - a) / 255;
+    let out = a + below;
+    if out == 0 {
+        return base;
+    }
+    let mix = |b: u8, t: u8| ((t as u32 * a + b as u32 * below) / out) as u8;
+    Color::new(mix(base.r, top.r), mix(base.g, top.g), mix(base.b, top.b), out as u8)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
 
-        // Tamaño fijo de la máscara (cuadrada). 512 o 1024 según VRAM y suavidad.
-        const MASK_SIZE: i32 = 512;
-        let mut img = Image::gen_image_color(MASK_SIZE, MASK_SIZE, Color::new(0,0,0,0));
-
-        // centro y radio máximo en la imagen de máscara
-        let cx = (MASK_SIZE / 2) as f32;
-        let cy = (MASK_SIZE / 2) as f32;
-        let max_r = (MASK_SIZE as f32) / 2.0;
-
-        // Factor gamma para el gradiente (ajusta suavidad)
-        let gamma = 1.0_f32;
-
-        for y in 0..MASK_SIZE {
-            for x in 0..MASK_SIZE {
-                let dx = x as f32 + 0.5 - cx;
-                let dy = y as f32 + 0.5 - cy;
-                let dist = (dx*dx + dy*dy).sqrt();
-                let t = (dist / max_r).min(1.0); // 0 centro, 1 borde
-                // máscara alpha: 0 en centro, 1 en borde -> queremos alpha proporcional a t^gamma
-                let mask_alpha_f = t.powf(gamma);
-                let mask_alpha = (mask_alpha_f * 255.0).round().clamp(0.0, 255.0) as u8;
-
-                // Color negro con alpha = mask_alpha
-                let px_color = Color::new(0, 0, 0, mask_alpha);
-                Image::draw_pixel(&mut img, x, y, px_color);
+    /// HUD de prueba: rectángulo opaco, panel semitransparente y texto encima.
+    fn draw_hud(framebuffer: &mut Framebuffer) {
+        framebuffer.fill_rect(4, 4, 20, 6, Color::new(200, 30, 30, 255));
+        framebuffer.blend_rect(10, 12, 40, 20, Color::new(0, 0, 0, 140));
+        framebuffer.blend_rect(30, 20, 25, 15, Color::new(255, 255, 255, 90));
+        framebuffer.draw_text_aligned("HUD", 12, 14, 11, Color::new(255, 230, 120, 200), Align::Left);
+    }
+
+    #[test]
+    fn layer_over_scaled_scene_matches_drawing_on_top() {
+        // escena a media resolución con un degradé para que se note si algo se corre
+        let mut scene = Framebuffer::new(32, 24, Color::BLACK);
+        for y in 0..24 {
+            for x in 0..32 {
+                scene.put_pixel(x, y, Color::new((x * 8) as u8, (y * 10) as u8, 90, 255));
             }
         }
 
-        // Crear textura GPU desde la image
-        if let Ok(tex) = window.load_texture_from_image(raylib_thread, &img) {
-            self.mask_texture = Some(tex);
-            // liberamos la image (img sale de scope)
-        } else {
-            // Si falla, dejamos mask_texture = None y no usaremos la máscara
-            eprintln!("Failed to create mask_texture from image");
+        // como antes: escalar en CPU y dibujar el HUD encima
+        let mut direct = Framebuffer::new(64, 48, Color::BLACK);
+        direct.blit_scaled(&scene);
+        draw_hud(&mut direct);
+
+        // como ahora: HUD en una capa transparente, juntada al presentar
+        let mut overlay = Framebuffer::new(64, 48, Color::BLACK);
+        overlay.clear_transparent();
+        draw_hud(&mut overlay);
+        let layered = Framebuffer::composed(&scene, &overlay);
+
+        for (a, b) in direct.pixels().iter().zip(layered.pixels()) {
+            let close = |p: u8, q: u8| p.abs_diff(q) <= 1;
+            assert!(close(a.r, b.r) && close(a.g, b.g) && close(a.b, b.b) && b.a == 255, "{:?} != {:?}", a, b);
         }
     }
 
-     pub fn queue_health(&mut self, current: i32, max: i32) {
-        self.health_to_draw = Some((current, max));
+    #[test]
+    fn blend_over_an_opaque_base_stays_opaque() {
+        let base = Color::new(100, 100, 100, 255);
+        assert_eq!(blend_over(base, Color::new(200, 0, 0, 0)), base);
+        assert_eq!(blend_over(base, Color::new(200, 0, 0, 255)), Color::new(200, 0, 0, 255));
+        assert_eq!(blend_over(base, Color::new(200, 0, 0, 128)).a, 255);
+        // sobre transparente queda el color de arriba con su alpha
+        assert_eq!(blend_over(Color::new(0, 0, 0, 0), Color::new(10, 20, 30, 77)), Color::new(10, 20, 30, 77));
     }
-    
 }
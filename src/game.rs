@@ -0,0 +1,265 @@
+use raylib::prelude::*;
+use std::f32::consts::PI;
+
+use crate::decals::Decals;
+use crate::enemy::Enemy;
+use crate::light::LightSet;
+use crate::maze::{load_maze, Maze};
+use crate::player::Player;
+
+// Lado de una celda del laberinto, en px del mundo
+pub const BLOCK_SIZE: usize = 100;
+// Distancia (px) a la que Jack alcanza al jugador
+const COLLISION_RADIUS: f32 = 28.0;
+// Segundos sin recibir daño después de un golpe
+pub const INVULN_DURATION: f32 = 1.5;
+const MAX_LIVES: i32 = 3;
+// Velocidad de Jack (px por paso) en el primer piso y cuánto sube por cada piso superado
+const BASE_ENEMY_SPEED: f32 = 2.9;
+const ENEMY_SPEED_PER_LEVEL: f32 = 0.2;
+// Dónde está Jack al empezar la partida y al reiniciarla (px del mundo)
+const JACK_START: (f32, f32) = (375.0, 400.0);
+const JACK_RESTART: (f32, f32) = (250.0, 250.0);
+
+/// Lo que pasó en un tick y cada interfaz muestra a su manera (sonido, partículas, pantallas).
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Event {
+    Hit,          // Jack alcanzó al jugador y le quitó una vida (le quedan más)
+    Caught,       // se quedó sin vidas
+    LevelChanged, // pasó al piso siguiente
+    Escaped,      // usó la salida del último piso
+}
+
+/// Una partida: piso actual, jugador, enemigos y vidas, con las reglas de cada tick.
+/// No sabe de ventanas ni de teclas; el juego con ventana y el modo terminal la manejan igual.
+pub struct Game {
+    levels: Vec<i32>,
+    level_index: usize,
+    pub maze: Maze,
+    pub lights: LightSet,
+    pub decals: Decals,
+    pub player: Player,
+    pub enemies: Vec<Enemy>,
+    pub lives: i32,
+    pub max_lives: i32,
+    invuln_timer: f32,
+    levels_passed: usize,
+}
+
+impl Game {
+    /// Partida nueva en `start_level` (o en el primero de `levels` si no está).
+    pub fn new(levels: &[i32], start_level: i32) -> Self {
+        let level_index = levels.iter().position(|&lv| lv == start_level).unwrap_or(0);
+        let level = levels.get(level_index).copied().unwrap_or(start_level);
+        let (maze, lights, decals) = load_level(level);
+        let player = Player { pos: spawn_point(&maze), a: PI / 3.0, fov: PI / 2.0 };
+        let enemies = vec![Enemy::jack(JACK_START.0, JACK_START.1)];
+        Game {
+            levels: levels.to_vec(),
+            level_index,
+            maze,
+            lights,
+            decals,
+            player,
+            enemies,
+            lives: MAX_LIVES,
+            max_lives: MAX_LIVES,
+            invuln_timer: 0.0,
+            levels_passed: 0,
+        }
+    }
+
+    pub fn current_level(&self) -> i32 {
+        self.levels.get(self.level_index).copied().unwrap_or(0)
+    }
+
+    /// Cuánto queda de invulnerabilidad (1 recién golpeado, 0 sin protección).
+    pub fn invulnerability(&self) -> f32 {
+        self.invuln_timer / INVULN_DURATION
+    }
+
+    /// Avanza `dt` segundos: mueve a los enemigos y resuelve el contacto con el jugador.
+    /// `steps` son los pasos de Jack por tick (1 a 60 fps; más si la interfaz va más lenta).
+    pub fn update(&mut self, dt: f32, steps: f32) -> Option<Event> {
+        let speed = (BASE_ENEMY_SPEED + ENEMY_SPEED_PER_LEVEL * self.levels_passed as f32) * steps;
+        for enemy in self.enemies.iter_mut() {
+            enemy.update(&self.player, &self.maze, BLOCK_SIZE, speed);
+        }
+
+        self.invuln_timer = (self.invuln_timer - dt).max(0.0);
+        if self.invuln_timer > 0.0 || self.lives <= 0 {
+            return None;
+        }
+        let player = self.player.pos;
+        let caught = self.enemies.iter().any(|e| {
+            let (dx, dy) = (e.pos.x - player.x, e.pos.y - player.y);
+            (dx * dx + dy * dy).sqrt() <= COLLISION_RADIUS
+        });
+        if !caught {
+            return None;
+        }
+        self.lives -= 1;
+        self.invuln_timer = INVULN_DURATION;
+        Some(if self.lives <= 0 { Event::Caught } else { Event::Hit })
+    }
+
+    /// Si el jugador está parado en una salida ('g').
+    pub fn at_exit(&self) -> bool {
+        let (i, j) = self.player_cell();
+        self.maze.get(j).and_then(|row| row.get(i)).map_or(false, |&c| c == 'g')
+    }
+
+    /// Usa la salida en la que está parado el jugador: pasa al piso siguiente
+    /// (`LevelChanged`) o, si era el último, termina (`Escaped`). Si no hay salida, nada.
+    pub fn use_exit(&mut self) -> Option<Event> {
+        if !self.at_exit() {
+            return None;
+        }
+        if self.level_index + 1 >= self.levels.len() {
+            return Some(Event::Escaped);
+        }
+
+        let exit_cell = self.player_cell();
+        self.level_index += 1;
+        (self.maze, self.lights, self.decals) = load_level(self.current_level());
+        self.player.pos = spawn_after_exit(&self.maze, exit_cell);
+        self.player.a = PI / 3.0;
+        self.enemies = spawn_enemies(&self.maze, self.player.pos);
+        self.lives = self.max_lives;
+        self.invuln_timer = 0.0;
+        self.levels_passed += 1;
+        Some(Event::LevelChanged)
+    }
+
+    /// Vuelve a empezar desde el primer piso, con las vidas llenas: el jugador en la salida
+    /// (o la celda libre más cercana al centro) y Jack en su punto de reinicio.
+    pub fn restart(&mut self) {
+        self.level_index = 0;
+        (self.maze, self.lights, self.decals) = load_level(self.current_level());
+        self.player.pos = match crate::find_tile(&self.maze, 'g') {
+            Some((gi, gj)) => crate::tile_center_pos(gi, gj, BLOCK_SIZE),
+            None => crate::find_nearest_free_to_center(&self.maze, BLOCK_SIZE),
+        };
+        self.enemies = vec![Enemy::jack(JACK_RESTART.0, JACK_RESTART.1)];
+        self.lives = self.max_lives;
+        self.invuln_timer = 0.0;
+        self.levels_passed = 0;
+    }
+
+    fn player_cell(&self) -> (usize, usize) {
+        let i = (self.player.pos.x / BLOCK_SIZE as f32).floor().max(0.0) as usize;
+        let j = (self.player.pos.y / BLOCK_SIZE as f32).floor().max(0.0) as usize;
+        (i, j)
+    }
+}
+
+/// Laberinto, luces y calcomanías de un piso.
+fn load_level(level: i32) -> (Maze, LightSet, Decals) {
+    let maze = load_maze(crate::maze_filename_for_level(level));
+    let lights = LightSet::load(&crate::lights_filename_for_level(level), &maze, BLOCK_SIZE);
+    let decals = Decals::load(&crate::decals_filename_for_level(level));
+    (maze, lights, decals)
+}
+
+/// Dónde empieza el jugador en un piso: la 's', si no la 'g', si no una celda libre.
+fn spawn_point(maze: &Maze) -> Vector2 {
+    if let Some((si, sj)) = crate::find_tile(maze, 's') {
+        crate::tile_center_pos(si, sj, BLOCK_SIZE)
+    } else if let Some((gi, gj)) = crate::find_tile(maze, 'g') {
+        crate::tile_center_pos(gi, gj, BLOCK_SIZE)
+    } else {
+        let corner = Vector2::new((BLOCK_SIZE / 2) as f32, (BLOCK_SIZE / 2) as f32);
+        crate::find_spawn_reachable(maze, BLOCK_SIZE, corner, 0)
+    }
+}
+
+/// Dónde aparece el jugador al llegar por la salida `exit_cell` del piso anterior: la 's' del
+/// piso nuevo si tiene, si no la misma celda (o la libre más cercana), como si bajara la escalera.
+fn spawn_after_exit(maze: &Maze, exit_cell: (usize, usize)) -> Vector2 {
+    if let Some((si, sj)) = crate::find_tile(maze, 's') {
+        return crate::tile_center_pos(si, sj, BLOCK_SIZE);
+    }
+    let h = maze.len();
+    let w = maze.first().map_or(0, |row| row.len());
+    if w == 0 || h == 0 {
+        return crate::find_nearest_free_to_center(maze, BLOCK_SIZE);
+    }
+    let (i, j) = (exit_cell.0.min(w - 1), exit_cell.1.min(h - 1));
+    match maze[j].get(i) {
+        Some(' ') | Some('g') if (i, j) == exit_cell => crate::tile_center_pos(i, j, BLOCK_SIZE),
+        _ => crate::find_nearest_free_around(maze, BLOCK_SIZE, i, j, 8),
+    }
+}
+
+/// Jack en una celda alcanzable a unas celdas del jugador.
+fn spawn_enemies(maze: &Maze, player_pos: Vector2) -> Vec<Enemy> {
+    let spawn = crate::find_spawn_reachable(maze, BLOCK_SIZE, player_pos, 3);
+    vec![Enemy::jack(spawn.x, spawn.y)]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Partida en `level` con Jack encima del jugador.
+    fn cornered(level: i32) -> Game {
+        let mut game = Game::new(crate::LEVELS, level);
+        game.enemies[0].pos = game.player.pos;
+        game
+    }
+
+    fn go_to_exit(game: &mut Game) {
+        let (gi, gj) = crate::find_tile(&game.maze, 'g').expect("todos los pisos tienen salida");
+        game.player.pos = crate::tile_center_pos(gi, gj, BLOCK_SIZE);
+    }
+
+    #[test]
+    fn contact_takes_one_life_then_protects() {
+        let mut game = cornered(3);
+        assert_eq!(game.update(1.0 / 60.0, 1.0), Some(Event::Hit));
+        assert_eq!(game.lives, 2);
+        // mientras dura la invulnerabilidad no hay otro golpe
+        game.enemies[0].pos = game.player.pos;
+        assert_eq!(game.update(INVULN_DURATION / 2.0, 1.0), None);
+        assert!(game.invulnerability() > 0.0);
+
+        game.enemies[0].pos = game.player.pos;
+        assert_eq!(game.update(INVULN_DURATION, 1.0), Some(Event::Hit));
+        game.enemies[0].pos = game.player.pos;
+        assert_eq!(game.update(INVULN_DURATION, 1.0), Some(Event::Caught));
+        assert_eq!(game.lives, 0);
+        assert_eq!(game.update(INVULN_DURATION, 1.0), None);
+    }
+
+    #[test]
+    fn exit_advances_levels_and_refills_lives() {
+        let mut game = cornered(3);
+        game.update(1.0 / 60.0, 1.0);
+        assert_eq!(game.lives, 2);
+        // lejos de la 'g' la salida no hace nada
+        game.player.pos = crate::tile_center_pos(10, 1, BLOCK_SIZE);
+        assert!(!game.at_exit());
+        assert_eq!(game.use_exit(), None);
+
+        go_to_exit(&mut game);
+        assert_eq!(game.use_exit(), Some(Event::LevelChanged));
+        assert_eq!(game.current_level(), 4);
+        assert_eq!(game.lives, game.max_lives);
+        go_to_exit(&mut game);
+        assert_eq!(game.use_exit(), Some(Event::LevelChanged));
+        assert_eq!(game.current_level(), 5);
+    }
+
+    #[test]
+    fn last_exit_escapes_and_restart_goes_back() {
+        let mut game = Game::new(crate::LEVELS, 7);
+        go_to_exit(&mut game);
+        assert_eq!(game.use_exit(), Some(Event::Escaped));
+        game.restart();
+        assert_eq!(game.current_level(), crate::LEVELS[0]);
+        assert_eq!(game.lives, game.max_lives);
+        // al reiniciar, el jugador queda en la salida del primer piso y Jack en su punto fijo
+        assert!(game.at_exit());
+        assert_eq!((game.enemies[0].pos.x, game.enemies[0].pos.y), JACK_RESTART);
+    }
+}
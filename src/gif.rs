@@ -0,0 +1,242 @@
+use raylib::prelude::*;
+use std::fs::File;
+use std::io::{self, BufWriter, Write};
+
+use crate::palette::Palette;
+
+// Tabla de colores global de 256 entradas (8 bits por píxel)
+const COLOR_BITS: u8 = 8;
+// Código LZW más largo que admite el formato
+const MAX_CODE_BITS: u32 = 12;
+
+/// GIF animado que se escribe a disco cuadro a cuadro (no se guarda nada en memoria).
+/// Los colores salen de una paleta de 256 armada con el primer cuadro.
+pub struct GifWriter {
+    out: BufWriter<File>,
+    palette: Option<Palette>,
+    width: u16,
+    height: u16,
+    delay: u16, // centésimas de segundo entre cuadros
+    pub frames: usize,
+}
+
+impl GifWriter {
+    /// Crea el archivo; cada cuadro dura `delay` centésimas de segundo.
+    pub fn create(path: &str, width: i32, height: i32, delay: u16) -> io::Result<Self> {
+        let (Ok(width), Ok(height)) = (u16::try_from(width), u16::try_from(height)) else {
+            return Err(io::Error::new(io::ErrorKind::InvalidInput, "el GIF mide como mucho 65535 px de lado"));
+        };
+        Ok(GifWriter { out: BufWriter::new(File::create(path)?), palette: None, width, height, delay, frames: 0 })
+    }
+
+    /// Agrega un cuadro (`pixels` fila por fila, del tamaño del GIF).
+    pub fn add_frame(&mut self, pixels: &[Color]) -> io::Result<()> {
+        if pixels.len() != self.width as usize * self.height as usize {
+            return Err(io::Error::new(io::ErrorKind::InvalidInput, "el cuadro no mide lo mismo que el GIF"));
+        }
+        if self.palette.is_none() {
+            // con una muestra del primer cuadro alcanza para armar la paleta
+            let samples: Vec<Color> = pixels.iter().step_by(7).copied().collect();
+            let palette = Palette::from_samples(&samples, false);
+            self.write_header(&palette)?;
+            self.palette = Some(palette);
+        }
+        let Some(palette) = &self.palette else {
+            return Ok(());
+        };
+        let indices: Vec<u8> = pixels.iter().map(|&c| palette.nearest(c)).collect();
+
+        // control gráfico: duración del cuadro
+        let [d0, d1] = self.delay.to_le_bytes();
+        self.out.write_all(&[0x21, 0xf9, 4, 0, d0, d1, 0, 0])?;
+        // descriptor de imagen: todo el lienzo, sin tabla local
+        let [w0, w1] = self.width.to_le_bytes();
+        let [h0, h1] = self.height.to_le_bytes();
+        self.out.write_all(&[0x2c, 0, 0, 0, 0, w0, w1, h0, h1, 0])?;
+
+        self.out.write_all(&[COLOR_BITS])?;
+        // los datos van en bloques de hasta 255 bytes, terminados en un bloque vacío
+        for block in lzw_encode(&indices, COLOR_BITS).chunks(255) {
+            self.out.write_all(&[block.len() as u8])?;
+            self.out.write_all(block)?;
+        }
+        self.out.write_all(&[0])?;
+        self.frames += 1;
+        Ok(())
+    }
+
+    /// Cierra el archivo. Sin cuadros queda un GIF vacío de 1 cuadro negro.
+    pub fn finish(mut self) -> io::Result<()> {
+        if self.palette.is_none() {
+            let black = vec![Color::BLACK; self.width as usize * self.height as usize];
+            self.add_frame(&black)?;
+        }
+        self.out.write_all(&[0x3b])?;
+        self.out.flush()
+    }
+
+    fn write_header(&mut self, palette: &Palette) -> io::Result<()> {
+        self.out.write_all(b"GIF89a")?;
+        let [w0, w1] = self.width.to_le_bytes();
+        let [h0, h1] = self.height.to_le_bytes();
+        // tabla global presente, 8 bits de color, 2^(7+1) = 256 entradas
+        self.out.write_all(&[w0, w1, h0, h1, 0xf7, 0, 0])?;
+        let mut table = [0u8; 256 * 3];
+        for (i, c) in palette.colors().iter().enumerate().take(256) {
+            table[i * 3..i * 3 + 3].copy_from_slice(&[c.r, c.g, c.b]);
+        }
+        self.out.write_all(&table)?;
+        // repetir para siempre (extensión NETSCAPE2.0)
+        self.out.write_all(&[0x21, 0xff, 11])?;
+        self.out.write_all(b"NETSCAPE2.0")?;
+        self.out.write_all(&[3, 1, 0, 0, 0])
+    }
+}
+
+/// Comprime `indices` con el LZW de los GIF (códigos de largo variable, empaquetados desde el bit bajo).
+fn lzw_encode(indices: &[u8], min_bits: u8) -> Vec<u8> {
+    let clear = 1u32 << min_bits;
+    let end = clear + 1;
+    // table[prefijo * 256 + siguiente] = código (0 = no está)
+    let mut table = vec![0u16; 4096 * 256];
+    let mut next = end + 1;
+    let mut bits = min_bits as u32 + 1;
+
+    let mut out = Vec::new();
+    let (mut acc, mut acc_bits) = (0u32, 0u32);
+    let mut emit = |code: u32, bits: u32, out: &mut Vec<u8>| {
+        acc |= code << acc_bits;
+        acc_bits += bits;
+        while acc_bits >= 8 {
+            out.push(acc as u8);
+            acc >>= 8;
+            acc_bits -= 8;
+        }
+    };
+
+    emit(clear, bits, &mut out);
+    let Some((&first, rest)) = indices.split_first() else {
+        emit(end, bits, &mut out);
+        emit(0, 7, &mut out);
+        return out;
+    };
+    let mut prefix = first as u32;
+    for &k in rest {
+        let slot = (prefix * 256 + k as u32) as usize;
+        if table[slot] != 0 {
+            prefix = table[slot] as u32;
+            continue;
+        }
+        emit(prefix, bits, &mut out);
+        if next < 1 << MAX_CODE_BITS {
+            // el lector pasa a códigos más largos cuando su tabla llega a 2^bits
+            if next >= 1 << bits {
+                bits += 1;
+            }
+            table[slot] = next as u16;
+            next += 1;
+        } else {
+            // tabla llena: se empieza de nuevo
+            emit(clear, bits, &mut out);
+            table.fill(0);
+            next = end + 1;
+            bits = min_bits as u32 + 1;
+        }
+        prefix = k as u32;
+    }
+    emit(prefix, bits, &mut out);
+    emit(end, bits, &mut out);
+    // completar el último byte
+    emit(0, 7, &mut out);
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Decodificador LZW mínimo para comprobar el codificador.
+    fn lzw_decode(data: &[u8], min_bits: u8) -> Vec<u8> {
+        let clear = 1usize << min_bits;
+        let end = clear + 1;
+        let reset = || (0..clear).map(|i| vec![i as u8]).chain([Vec::new(), Vec::new()]).collect::<Vec<Vec<u8>>>();
+        let mut dict = reset();
+        let mut bits = min_bits as usize + 1;
+        let mut out = Vec::new();
+        let mut prev: Option<usize> = None;
+        let mut pos = 0usize;
+        loop {
+            let mut code = 0usize;
+            for b in 0..bits {
+                let bit = (data[(pos + b) / 8] >> ((pos + b) % 8)) & 1;
+                code |= (bit as usize) << b;
+            }
+            pos += bits;
+            if code == clear {
+                dict = reset();
+                bits = min_bits as usize + 1;
+                prev = None;
+                continue;
+            }
+            if code == end {
+                return out;
+            }
+            let entry = match (dict.get(code), prev) {
+                (Some(e), _) => e.clone(),
+                (None, Some(p)) => {
+                    let mut e = dict[p].clone();
+                    e.push(dict[p][0]);
+                    e
+                }
+                (None, None) => panic!("código {} sin prefijo", code),
+            };
+            if let Some(p) = prev {
+                if dict.len() < 4096 {
+                    let mut e = dict[p].clone();
+                    e.push(entry[0]);
+                    dict.push(e);
+                }
+            }
+            if dict.len() == 1 << bits && bits < 12 {
+                bits += 1;
+            }
+            out.extend_from_slice(&entry);
+            prev = Some(code);
+        }
+    }
+
+    #[test]
+    fn lzw_round_trip() {
+        let cases: Vec<Vec<u8>> = vec![
+            vec![],
+            vec![7],
+            vec![0; 10_000],
+            (0..50_000u32).map(|i| (i * 7 % 251) as u8).collect(),
+            // ruido: llena la tabla varias veces
+            (0..100_000u32).map(|i| crate::postfx::hash(i, 3, 9) as u8).collect(),
+        ];
+        for data in cases {
+            assert_eq!(lzw_decode(&lzw_encode(&data, 8), 8), data);
+        }
+    }
+
+    #[test]
+    fn writes_header_frames_and_trailer() {
+        let path = std::env::temp_dir().join(format!("laberinto_{}_gif_test.gif", std::process::id()));
+        let path = path.to_string_lossy();
+        let mut gif = GifWriter::create(&path, 4, 2, 5).unwrap();
+        gif.add_frame(&[Color::RED; 8]).unwrap();
+        gif.add_frame(&[Color::BLACK; 8]).unwrap();
+        assert!(gif.add_frame(&[Color::BLACK; 3]).is_err());
+        assert_eq!(gif.frames, 2);
+        gif.finish().unwrap();
+
+        let bytes = std::fs::read(&*path).unwrap();
+        assert_eq!(&bytes[..6], b"GIF89a");
+        assert_eq!(&bytes[6..10], &[4, 0, 2, 0]);
+        assert_eq!(bytes.last(), Some(&0x3b));
+        // un descriptor de imagen por cuadro
+        assert_eq!(bytes.windows(2).filter(|w| w == &[0x21, 0xf9]).count(), 2);
+        std::fs::remove_file(&*path).unwrap();
+    }
+}
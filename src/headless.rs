@@ -0,0 +1,279 @@
+use raylib::prelude::*;
+use std::f32::consts::PI;
+use std::fs;
+use std::path::Path;
+
+use crate::decals::Decals;
+use crate::enemy::Enemy;
+use crate::framebuffer::Framebuffer;
+use crate::hud::{Hud, HudState, Widget};
+use crate::light::{Flashlight, LightSet};
+use crate::maze::load_maze;
+use crate::player::Player;
+use crate::present::HeadlessPresenter;
+use crate::sprite::SpriteLimits;
+use crate::textures::{self, TextureManager};
+
+// Tamaño por defecto de las imágenes sin ventana (pequeño para que las referencias pesen poco)
+const DEFAULT_W: i32 = 400;
+const DEFAULT_H: i32 = 280;
+// Diferencia máxima por canal para considerar iguales dos píxeles
+const TOLERANCE: u8 = 2;
+
+/// Una escena fija: nivel, pose del jugador (posición en px del mundo, ángulo en grados) y enemigos.
+pub struct SceneSpec {
+    pub level: i32,
+    pub x: f32,
+    pub y: f32,
+    pub angle_deg: f32,
+    pub enemies: Vec<(f32, f32)>,
+}
+
+/// Dibuja la escena como en el juego (vista 3D, sprites y minimapa) sin ventana ni GPU.
+pub fn render_scene(spec: &SceneSpec, texture_cache: &TextureManager, width: i32, height: i32) -> Framebuffer {
+    let block_size = 100;
+    let level = spec.level;
+    let maze = load_maze(crate::maze_filename_for_level(level));
+    let mut lights = LightSet::load(&crate::lights_filename_for_level(level), &maze, block_size);
+    let decals = Decals::load(&crate::decals_filename_for_level(level));
+
+    let player = Player {
+        pos: Vector2::new(spec.x, spec.y),
+        a: spec.angle_deg.to_radians(),
+        fov: PI / 2.0,
+    };
+    let enemies: Vec<Enemy> = spec.enemies.iter().map(|&(x, y)| Enemy::jack(x, y)).collect();
+
+    // reloj congelado en 0 para que la imagen no dependa del momento
+    let mut flashlight = Flashlight::new();
+    flashlight.follow_enemies(&player, &enemies);
+    lights.update(0.0, 0.0, &player, &enemies, &maze, block_size);
+
+    let mut framebuffer = Framebuffer::new(width, height, Color::new(50, 50, 100, 255));
+    let mut depth_buffer = vec![f32::INFINITY; width as usize];
+    crate::render_world(&mut framebuffer, &player, &maze, texture_cache, &mut depth_buffer, level as usize, &flashlight, &lights, &decals, 0.0, 1);
+    let sprites = crate::world_sprites(&player, &maze, &enemies, &lights, level as usize);
+    crate::sprite::render_sprites(&mut framebuffer, &player, &sprites, texture_cache, &depth_buffer, &flashlight, &lights, SpriteLimits::default());
+    // del HUD solo el minimapa: FPS y demás cambiarían la imagen de una corrida a otra
+    let hud_state = HudState {
+        fps: 0,
+        level,
+        lives: 0,
+        max_lives: 0,
+        maze: &maze,
+        player: &player,
+        enemies: &enemies,
+        block_size,
+        view_3d: true,
+        recording: false,
+        message: None,
+    };
+    Hud::with(&[Widget::Minimap]).draw(&mut framebuffer, &hud_state);
+
+    framebuffer
+}
+
+/// Cuenta los píxeles que difieren de la imagen de referencia en `path` (más de TOLERANCE por canal).
+pub fn compare_with_file(framebuffer: &Framebuffer, path: &str) -> Result<usize, String> {
+    let (reference, w, h) = textures::load_colors(path)?;
+    if w != framebuffer.width || h != framebuffer.height {
+        return Err(format!(
+            "{}: mide {}x{}, se esperaba {}x{}",
+            path, w, h, framebuffer.width, framebuffer.height
+        ));
+    }
+
+    let differs = |a: u8, b: u8| a.abs_diff(b) > TOLERANCE;
+    Ok(framebuffer
+        .pixels()
+        .iter()
+        .zip(reference.iter())
+        .filter(|(a, b)| differs(a.r, b.r) || differs(a.g, b.g) || differs(a.b, b.b) || differs(a.a, b.a))
+        .count())
+}
+
+/// Atiende los modos sin ventana. Devuelve `None` si los argumentos no piden ninguno
+/// (y el juego arranca normal), o el código de salida del proceso.
+///
+///   --render <nivel> <x> <y> <ángulo> <salida.png> [--size <ancho> <alto>] [--enemy <x> <y>]...
+///   --golden <casos.txt> [--update]
+///   --terminal [nivel]
+pub fn run_cli(args: &[String]) -> Option<i32> {
+    let args: Vec<&str> = args.iter().skip(1).map(|a| a.as_str()).collect();
+    match args.first() {
+        Some(&"--render") => Some(match cli_render(&args[1..]) {
+            Ok(()) => 0,
+            Err(e) => {
+                eprintln!("{}", e);
+                eprintln!("uso: --render <nivel> <x> <y> <ángulo> <salida.png> [--size <ancho> <alto>] [--enemy <x> <y>]...");
+                2
+            }
+        }),
+        Some(&"--golden") => Some(match args.get(1) {
+            Some(cases) => cli_golden(cases, args.contains(&"--update")),
+            None => {
+                eprintln!("uso: --golden <casos.txt> [--update]");
+                2
+            }
+        }),
+        Some(&"--terminal") => Some(match args.get(1).map(|l| parse::<i32>(l)).transpose() {
+            Ok(level) => crate::terminal::run(crate::LEVELS, level.unwrap_or(crate::LEVELS[0])),
+            Err(e) => {
+                eprintln!("{}", e);
+                eprintln!("uso: --terminal [nivel]");
+                2
+            }
+        }),
+        _ => None,
+    }
+}
+
+fn cli_render(args: &[&str]) -> Result<(), String> {
+    let [level, x, y, angle, out, rest @ ..] = args else {
+        return Err("faltan argumentos".to_string());
+    };
+    let mut spec = SceneSpec {
+        level: parse(level)?,
+        x: parse(x)?,
+        y: parse(y)?,
+        angle_deg: parse(angle)?,
+        enemies: Vec::new(),
+    };
+    let (mut width, mut height) = (DEFAULT_W, DEFAULT_H);
+
+    let mut rest = rest;
+    while !rest.is_empty() {
+        match rest {
+            ["--size", w, h, tail @ ..] => {
+                width = parse(w)?;
+                height = parse(h)?;
+                rest = tail;
+            }
+            ["--enemy", ex, ey, tail @ ..] => {
+                spec.enemies.push((parse(ex)?, parse(ey)?));
+                rest = tail;
+            }
+            _ => return Err(format!("argumento inesperado: {}", rest[0])),
+        }
+    }
+    if width <= 0 || height <= 0 {
+        return Err("el tamaño debe ser positivo".to_string());
+    }
+
+    let texture_cache = TextureManager::new();
+    let mut framebuffer = render_scene(&spec, &texture_cache, width, height);
+    framebuffer.swap_buffers(&mut HeadlessPresenter::new(Some(out.to_string())));
+    println!("Escena guardada en {}", out);
+    Ok(())
+}
+
+/// Dibuja cada caso de `cases` y lo compara con `<carpeta de cases>/<nombre>.png`.
+/// Formato por línea: <nombre> <nivel> <x> <y> <ángulo> [<x_enemigo> <y_enemigo>]...
+/// Con `update`, (re)escribe las referencias en lugar de comparar.
+fn cli_golden(cases: &str, update: bool) -> i32 {
+    let text = match fs::read_to_string(cases) {
+        Ok(text) => text,
+        Err(e) => {
+            eprintln!("{}: {}", cases, e);
+            return 2;
+        }
+    };
+    let dir = Path::new(cases).parent().unwrap_or(Path::new("."));
+    let texture_cache = TextureManager::new();
+    let mut failed = 0;
+
+    for (n, line) in text.lines().enumerate() {
+        let line = line.split('#').next().unwrap_or("").trim();
+        if line.is_empty() {
+            continue;
+        }
+        let parts: Vec<&str> = line.split_whitespace().collect();
+        let spec = match parse_case(&parts) {
+            Ok(spec) => spec,
+            Err(e) => {
+                eprintln!("{}:{}: {}", cases, n + 1, e);
+                failed += 1;
+                continue;
+            }
+        };
+        let name = parts[0];
+        let reference = dir.join(format!("{}.png", name));
+        let reference = reference.to_string_lossy();
+
+        let framebuffer = render_scene(&spec, &texture_cache, DEFAULT_W, DEFAULT_H);
+        if update {
+            framebuffer.render_to_file(&reference);
+            println!("{}: referencia actualizada ({})", name, reference);
+            continue;
+        }
+
+        match compare_with_file(&framebuffer, &reference) {
+            Ok(0) => println!("{}: ok", name),
+            Ok(diff) => {
+                let actual = dir.join(format!("{}.actual.png", name));
+                framebuffer.render_to_file(&actual.to_string_lossy());
+                eprintln!("{}: {} píxeles distintos (ver {})", name, diff, actual.display());
+                failed += 1;
+            }
+            Err(e) => {
+                eprintln!("{}: {}", name, e);
+                failed += 1;
+            }
+        }
+    }
+
+    if failed > 0 { 1 } else { 0 }
+}
+
+fn parse_case(parts: &[&str]) -> Result<SceneSpec, String> {
+    let [_name, level, x, y, angle, enemies @ ..] = parts else {
+        return Err("se esperaba: <nombre> <nivel> <x> <y> <ángulo> [<x_enemigo> <y_enemigo>]...".to_string());
+    };
+    if enemies.len() % 2 != 0 {
+        return Err("cada enemigo necesita x e y".to_string());
+    }
+    Ok(SceneSpec {
+        level: parse(level)?,
+        x: parse(x)?,
+        y: parse(y)?,
+        angle_deg: parse(angle)?,
+        enemies: enemies
+            .chunks(2)
+            .map(|p| Ok((parse(p[0])?, parse(p[1])?)))
+            .collect::<Result<_, String>>()?,
+    })
+}
+
+fn parse<T: std::str::FromStr>(value: &str) -> Result<T, String> {
+    value.parse().map_err(|_| format!("valor inválido: {}", value))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn scenes_match_the_golden_references() {
+        let cases = "golden/cases.txt";
+        let text = fs::read_to_string(cases).expect("golden/cases.txt");
+        let texture_cache = TextureManager::new();
+
+        let mut failures = Vec::new();
+        for line in text.lines() {
+            let line = line.split('#').next().unwrap_or("").trim();
+            if line.is_empty() {
+                continue;
+            }
+            let parts: Vec<&str> = line.split_whitespace().collect();
+            let spec = parse_case(&parts).unwrap_or_else(|e| panic!("{}: {}", line, e));
+            let framebuffer = render_scene(&spec, &texture_cache, DEFAULT_W, DEFAULT_H);
+            match compare_with_file(&framebuffer, &format!("golden/{}.png", parts[0])) {
+                Ok(0) => {}
+                Ok(diff) => failures.push(format!("{}: {} píxeles distintos", parts[0], diff)),
+                Err(e) => failures.push(e),
+            }
+        }
+        // si el cambio en el render es a propósito: cargo run -- --golden golden/cases.txt --update
+        assert!(failures.is_empty(), "{}", failures.join("\n"));
+    }
+}
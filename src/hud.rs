@@ -0,0 +1,409 @@
+use raylib::prelude::*;
+
+use crate::enemy::Enemy;
+use crate::font::{self, Align};
+use crate::framebuffer::Framebuffer;
+use crate::maze::Maze;
+use crate::player::Player;
+
+// El HUD se diseñó para una ventana de 900 px de alto; a otros tamaños se escala
+// (con un mínimo para que siga siendo legible en la terminal).
+const REFERENCE_HEIGHT: f32 = 900.0;
+const MIN_SCALE: f32 = 0.35;
+const MARGIN: f32 = 10.0;
+
+/// Piezas del HUD.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Widget {
+    Fps,
+    Floor,     // "Piso N"
+    Message,   // aviso temporal (p.ej. "Captura guardada")
+    Recording, // "REC" mientras se graba una secuencia
+    Health,    // barras de vida
+    Minimap,
+}
+
+/// Borde o esquina de la pantalla al que se pega un widget. Los widgets con la misma ancla
+/// se apilan: hacia abajo los de arriba, hacia arriba los de abajo.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Anchor {
+    TopLeft,
+    Top,
+    TopRight,
+    BottomLeft,
+    Bottom,
+    BottomRight,
+}
+
+/// Lo que el HUD muestra en este frame (lo llena el bucle del juego).
+pub struct HudState<'a> {
+    pub fps: i32,
+    pub level: i32,
+    pub lives: i32,
+    pub max_lives: i32,
+    pub maze: &'a Maze,
+    pub player: &'a Player,
+    pub enemies: &'a [Enemy],
+    pub block_size: usize,
+    pub view_3d: bool, // el minimapa solo tiene sentido sobre la vista 3D
+    pub recording: bool,
+    pub message: Option<&'a str>,
+}
+
+/// Rectángulo en px: (x, y, ancho, alto).
+type Rect = (i32, i32, i32, i32);
+
+struct Slot {
+    widget: Widget,
+    anchor: Anchor,
+    enabled: bool,
+}
+
+pub struct Hud {
+    slots: Vec<Slot>,
+}
+
+impl Hud {
+    /// Disposición del juego, con todos los widgets encendidos.
+    pub fn new() -> Self {
+        let slot = |widget, anchor| Slot { widget, anchor, enabled: true };
+        Hud {
+            slots: vec![
+                slot(Widget::Fps, Anchor::TopLeft),
+                slot(Widget::Message, Anchor::TopLeft),
+                slot(Widget::Floor, Anchor::Top),
+                slot(Widget::Recording, Anchor::TopRight),
+                slot(Widget::Health, Anchor::BottomLeft),
+                slot(Widget::Minimap, Anchor::BottomRight),
+            ],
+        }
+    }
+
+    /// La misma disposición, pero solo con los widgets indicados.
+    pub fn with(widgets: &[Widget]) -> Self {
+        let mut hud = Hud::new();
+        for slot in hud.slots.iter_mut() {
+            slot.enabled = widgets.contains(&slot.widget);
+        }
+        hud
+    }
+
+    pub fn set_enabled(&mut self, widget: Widget, enabled: bool) {
+        for slot in self.slots.iter_mut().filter(|s| s.widget == widget) {
+            slot.enabled = enabled;
+        }
+    }
+
+    /// Dibuja el HUD del juego (todo menos los avisos pasajeros).
+    pub fn draw(&self, framebuffer: &mut Framebuffer, state: &HudState) {
+        self.draw_layer(framebuffer, state, false);
+    }
+
+    /// Dibuja los avisos pasajeros ("REC", mensajes). Va aparte para dibujarlos
+    /// después de guardar capturas, así no salen en ellas.
+    pub fn draw_notices(&self, framebuffer: &mut Framebuffer, state: &HudState) {
+        self.draw_layer(framebuffer, state, true);
+    }
+
+    fn draw_layer(&self, framebuffer: &mut Framebuffer, state: &HudState, notices: bool) {
+        let scale = hud_scale(framebuffer.height);
+        for (widget, (x, y, w, h)) in self.layout(framebuffer.width, framebuffer.height, state) {
+            let is_notice = matches!(widget, Widget::Message | Widget::Recording);
+            if is_notice == notices {
+                draw_widget(framebuffer, widget, state, scale, x, y, w, h);
+            }
+        }
+    }
+
+    /// Dónde va cada widget que se ve en este frame: (x, y, ancho, alto) en px de una pantalla
+    /// de `width`x`height`. El acomodo se calcula con todos los widgets, aunque solo se dibuje
+    /// una capa, para que cada uno quede siempre en el mismo lugar.
+    fn layout(&self, width: i32, height: i32, state: &HudState) -> Vec<(Widget, Rect)> {
+        let scale = hud_scale(height);
+        let margin = (MARGIN * scale).round() as i32;
+        // por ancla, cuánto ocupan ya los widgets apilados
+        let mut used = [0i32; 6];
+        let mut placed = Vec::new();
+
+        for slot in self.slots.iter().filter(|s| s.enabled) {
+            let Some((w, h)) = widget_size(slot.widget, state, scale) else {
+                continue;
+            };
+            let stacked = &mut used[slot.anchor as usize];
+            let x = match slot.anchor {
+                Anchor::TopLeft | Anchor::BottomLeft => margin,
+                Anchor::Top | Anchor::Bottom => (width - w) / 2,
+                Anchor::TopRight | Anchor::BottomRight => width - margin - w,
+            };
+            let y = match slot.anchor {
+                Anchor::TopLeft | Anchor::Top | Anchor::TopRight => margin + *stacked,
+                _ => height - margin - *stacked - h,
+            };
+            *stacked += h + margin;
+            placed.push((slot.widget, (x, y, w, h)));
+        }
+        placed
+    }
+}
+
+/// Escala del HUD para una pantalla de `height` px de alto.
+fn hud_scale(height: i32) -> f32 {
+    (height as f32 / REFERENCE_HEIGHT).max(MIN_SCALE)
+}
+
+fn scaled(value: f32, scale: f32) -> i32 {
+    (value * scale).round().max(1.0) as i32
+}
+
+fn widget_text(widget: Widget, state: &HudState) -> Option<(String, f32, Color)> {
+    match widget {
+        Widget::Fps => Some((format!("FPS: {}", state.fps), 20.0, Color::WHITE)),
+        Widget::Floor => Some((format!("Piso {}", state.level), 28.0, Color::YELLOW)),
+        Widget::Message => state.message.map(|m| (m.to_string(), 18.0, Color::WHITE)),
+        Widget::Recording => state.recording.then(|| ("REC".to_string(), 20.0, Color::RED)),
+        Widget::Health | Widget::Minimap => None,
+    }
+}
+
+/// Tamaño del widget en px, o None si en este frame no muestra nada.
+fn widget_size(widget: Widget, state: &HudState, scale: f32) -> Option<(i32, i32)> {
+    match widget {
+        Widget::Health => {
+            let lives = state.max_lives.max(0);
+            (lives > 0).then(|| (scaled(140.0, scale), lives * scaled(26.0, scale) - scaled(8.0, scale)))
+        }
+        Widget::Minimap => state.view_3d.then(|| (scaled(260.0, scale), scaled(160.0, scale))),
+        _ => widget_text(widget, state).map(|(text, size, _)| font::measure_text(&text, scaled(size, scale))),
+    }
+}
+
+fn draw_widget(framebuffer: &mut Framebuffer, widget: Widget, state: &HudState, scale: f32, x: i32, y: i32, w: i32, h: i32) {
+    match widget {
+        Widget::Health => draw_health_bars(framebuffer, state, scale, x, y, h),
+        Widget::Minimap => draw_minimap(framebuffer, state, x, y, w, h),
+        _ => {
+            if let Some((text, size, color)) = widget_text(widget, state) {
+                framebuffer.draw_text_aligned(&text, x, y, scaled(size, scale), color, Align::Left);
+            }
+        }
+    }
+}
+
+/// Barras de vida apiladas (una por vida), la primera abajo.
+fn draw_health_bars(framebuffer: &mut Framebuffer, state: &HudState, scale: f32, x: i32, y: i32, h: i32) {
+    let bar_w = scaled(140.0, scale);
+    let bar_h = scaled(18.0, scale);
+    let step = scaled(26.0, scale); // barra + separación
+    let inset = scaled(3.0, scale);
+
+    for i in 0..state.max_lives {
+        let by = y + h - bar_h - i * step;
+
+        // fondo de la barra (gris)
+        framebuffer.blend_rect(x, by, bar_w, bar_h, Color::new(60, 60, 60, 200));
+
+        // viva en verde; vacía en rojo tenue
+        let fill = if i < state.lives {
+            Color::new(40, 200, 40, 255)
+        } else {
+            Color::new(120, 0, 0, 200)
+        };
+        framebuffer.blend_rect(x + inset, by + inset, bar_w - inset * 2, bar_h - inset * 2, fill);
+    }
+}
+
+/// Minimapa en el recuadro (x, y, w, h): paredes, jugador con flecha de dirección y enemigos.
+fn draw_minimap(framebuffer: &mut Framebuffer, state: &HudState, x: i32, y: i32, w: i32, h: i32) {
+    let maze = state.maze;
+    let block_size = state.block_size;
+
+    // Dimensiones del mundo en píxeles (según laberinto)
+    let maze_h = maze.len();
+    let maze_w = if maze_h > 0 { maze[0].len() } else { 0 };
+    if maze_w == 0 || maze_h == 0 { return; }
+
+    let world_w = (maze_w * block_size) as f32;
+    let world_h = (maze_h * block_size) as f32;
+
+    // escala del mundo -> minimapa (mantener proporción)
+    let scale = (w as f32 / world_w).min(h as f32 / world_h);
+
+    // centrar el mapa dentro del recuadro minimapa
+    let inner_offset_x = x as f32 + (w as f32 - world_w * scale) / 2.0;
+    let inner_offset_y = y as f32 + (h as f32 - world_h * scale) / 2.0;
+
+    // fondo minimapa (semitransparente sobre la vista 3D)
+    framebuffer.blend_rect(x, y, w, h, Color::new(10, 10, 10, 220));
+
+    // Dibujar paredes (cada celda del maze que no sea ' ' ni 'g' será pared)
+    let wall_color = Color::new(160, 160, 160, 255);
+    for j in 0..maze_h {
+        for i in 0..maze_w {
+            let ch = maze[j][i];
+            if ch == ' ' || ch == 'g' || ch == 's' { continue; }
+            let cell_x = inner_offset_x + (i as f32) * (block_size as f32) * scale;
+            let cell_y = inner_offset_y + (j as f32) * (block_size as f32) * scale;
+            let size = (block_size as f32) * scale;
+            let ix0 = cell_x.floor() as i32;
+            let iy0 = cell_y.floor() as i32;
+            let ix1 = (cell_x + size).ceil() as i32;
+            let iy1 = (cell_y + size).ceil() as i32;
+            framebuffer.fill_rect(ix0, iy0, ix1 - ix0, iy1 - iy0, wall_color);
+        }
+    }
+
+    // Dibujar player como punto con flecha de dirección (escala apropiada)
+    let player = state.player;
+    let px = inner_offset_x + player.pos.x * scale;
+    let py = inner_offset_y + player.pos.y * scale;
+    let pxi = px as i32;
+    let pyi = py as i32;
+    framebuffer.fill_circle(pxi, pyi, 2, Color::WHITE);
+    // flecha/dirección proporcional al tamaño del minimapa
+    let dir_len = (block_size as f32).max(24.0) * scale; // una celda del mundo
+    let dir_x = px + player.a.cos() * dir_len;
+    let dir_y = py + player.a.sin() * dir_len;
+    framebuffer.draw_line(pxi, pyi, dir_x as i32, dir_y as i32, Color::WHITE);
+    // punta de la flecha: dos trazos cortos a ±150° de la dirección
+    let head_len = (dir_len * 0.4).max(3.0);
+    for side in [-1.0_f32, 1.0] {
+        let ha = player.a + side * std::f32::consts::PI * 5.0 / 6.0;
+        let hx = dir_x + ha.cos() * head_len;
+        let hy = dir_y + ha.sin() * head_len;
+        framebuffer.draw_line(dir_x as i32, dir_y as i32, hx as i32, hy as i32, Color::WHITE);
+    }
+
+    // Dibujar enemigos (punto 3x3)
+    let enemy_color = Color::new(220, 40, 40, 255);
+    for e in state.enemies {
+        let ex = inner_offset_x + e.pos.x * scale;
+        let ey = inner_offset_y + e.pos.y * scale;
+        framebuffer.fill_rect(ex as i32 - 1, ey as i32 - 1, 3, 3, enemy_color);
+    }
+
+    // Borde del minimapa
+    framebuffer.draw_rect(x, y, w, h, Color::new(220, 220, 220, 180));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::f32::consts::PI;
+
+    fn with_state<R>(message: Option<&str>, recording: bool, f: impl FnOnce(&HudState) -> R) -> R {
+        let maze: Maze = ["+---+", "|   |", "+---+"].iter().map(|r| r.chars().collect()).collect();
+        let player = Player { pos: Vector2::new(150.0, 150.0), a: 0.0, fov: PI / 2.0 };
+        let state = HudState {
+            fps: 60,
+            level: 3,
+            lives: 2,
+            max_lives: 3,
+            maze: &maze,
+            player: &player,
+            enemies: &[],
+            block_size: 100,
+            view_3d: true,
+            recording,
+            message,
+        };
+        f(&state)
+    }
+
+    fn rect(placed: &[(Widget, Rect)], widget: Widget) -> Option<Rect> {
+        placed.iter().find(|(w, _)| *w == widget).map(|&(_, r)| r)
+    }
+
+    #[test]
+    fn widgets_stack_from_their_anchor() {
+        with_state(Some("Captura guardada"), true, |state| {
+            let placed = Hud::new().layout(1200, 900, state);
+            let fps = rect(&placed, Widget::Fps).unwrap();
+            let message = rect(&placed, Widget::Message).unwrap();
+            // arriba a la izquierda: FPS y debajo el aviso, separados por el margen
+            assert_eq!((fps.0, fps.1), (10, 10));
+            assert_eq!((message.0, message.1), (10, 10 + fps.3 + 10));
+            // centrado arriba; a la derecha, pegado al borde
+            let floor = rect(&placed, Widget::Floor).unwrap();
+            assert_eq!((floor.0, floor.1), ((1200 - floor.2) / 2, 10));
+            let rec = rect(&placed, Widget::Recording).unwrap();
+            assert_eq!(rec.0 + rec.2, 1200 - 10);
+            // abajo: pegados al borde inferior
+            let health = rect(&placed, Widget::Health).unwrap();
+            assert_eq!((health.0, health.1 + health.3), (10, 900 - 10));
+            let minimap = rect(&placed, Widget::Minimap).unwrap();
+            assert_eq!((minimap.0 + minimap.2, minimap.1 + minimap.3), (1200 - 10, 900 - 10));
+        });
+
+        // abajo se apilan hacia arriba
+        let slot = |widget| Slot { widget, anchor: Anchor::BottomLeft, enabled: true };
+        let hud = Hud { slots: vec![slot(Widget::Fps), slot(Widget::Floor)] };
+        with_state(None, false, |state| {
+            let placed = hud.layout(1200, 900, state);
+            let fps = rect(&placed, Widget::Fps).unwrap();
+            let floor = rect(&placed, Widget::Floor).unwrap();
+            assert_eq!(fps.1 + fps.3, 900 - 10);
+            assert_eq!(floor.1 + floor.3, fps.1 - 10);
+        });
+    }
+
+    #[test]
+    fn small_screens_keep_a_minimum_scale() {
+        with_state(None, false, |state| {
+            let big = Hud::new().layout(1200, 900, state);
+            let tiny = Hud::new().layout(160, 60, state);
+            let health = rect(&tiny, Widget::Health).unwrap();
+            // a 60 px de alto la escala sería 0.07; se queda en MIN_SCALE
+            assert_eq!(health.0, (MARGIN * MIN_SCALE).round() as i32);
+            assert_eq!(health.2, scaled(140.0, MIN_SCALE));
+            assert!(health.2 < rect(&big, Widget::Health).unwrap().2);
+        });
+    }
+
+    #[test]
+    fn hidden_and_empty_widgets_take_no_space() {
+        with_state(Some("Captura guardada"), false, |state| {
+            // sin FPS, el aviso sube a la esquina
+            let mut hud = Hud::new();
+            hud.set_enabled(Widget::Fps, false);
+            let placed = hud.layout(1200, 900, state);
+            assert_eq!(rect(&placed, Widget::Fps), None);
+            assert_eq!(rect(&placed, Widget::Message).map(|r| (r.0, r.1)), Some((10, 10)));
+            // sin grabar no hay "REC"
+            assert_eq!(rect(&placed, Widget::Recording), None);
+
+            let only_message = Hud::with(&[Widget::Message]).layout(1200, 900, state);
+            assert_eq!(only_message.len(), 1);
+        });
+    }
+
+    #[test]
+    fn notices_and_hud_share_the_layout() {
+        with_state(Some("Captura guardada"), true, |state| {
+            let hud = Hud::new();
+            let placed = hud.layout(1200, 900, state);
+            let inside = |i: usize, widgets: &[Widget]| {
+                let (x, y) = ((i % 1200) as i32, (i / 1200) as i32);
+                widgets.iter().filter_map(|&w| rect(&placed, w)).any(|(rx, ry, rw, rh)| {
+                    x >= rx && x < rx + rw && y >= ry && y < ry + rh
+                })
+            };
+            let drawn = |fb: &Framebuffer| -> Vec<usize> {
+                fb.pixels().iter().enumerate().filter(|(_, c)| c.a != 0).map(|(i, _)| i).collect()
+            };
+
+            let mut notices = Framebuffer::new(1200, 900, Color::new(0, 0, 0, 0));
+            hud.draw_notices(&mut notices, state);
+            let notice_pixels = drawn(&notices);
+            assert!(!notice_pixels.is_empty());
+            assert!(notice_pixels.iter().all(|&i| inside(i, &[Widget::Message, Widget::Recording])));
+
+            // el resto del HUD, en los mismos lugares y sin tocar los de los avisos
+            let mut rest = Framebuffer::new(1200, 900, Color::new(0, 0, 0, 0));
+            hud.draw(&mut rest, state);
+            let rest_pixels = drawn(&rest);
+            assert!(!rest_pixels.is_empty());
+            let others = [Widget::Fps, Widget::Floor, Widget::Health, Widget::Minimap];
+            assert!(rest_pixels.iter().all(|&i| inside(i, &others)));
+        });
+    }
+}
@@ -0,0 +1,521 @@
+use raylib::prelude::*;
+use std::f32::consts::PI;
+use std::fs;
+use crate::caster::cast_ray_from;
+use crate::enemy::Enemy;
+use crate::maze::Maze;
+use crate::player::Player;
+
+/// Linterna del jugador: un cono a lo largo de `player.a` con caída por distancia.
+/// Se evalúa en el renderer (paredes, suelo, techo y sprites), no como máscara de pantalla.
+pub struct Flashlight {
+    pub half_angle: f32, // radio angular del cono (rad)
+    pub softness: f32,   // ancho (rad) del borde suave del cono
+    pub range: f32,      // distancia (px del mundo) a la que la luz cae a la mitad
+    pub intensity: f32,  // brillo en el centro del cono
+    pub ambient: f32,    // luz mínima fuera del cono
+}
+
+impl Flashlight {
+    pub fn new() -> Self {
+        Flashlight {
+            half_angle: PI / 4.0,
+            softness: PI / 12.0,
+            range: 600.0,
+            intensity: 1.0,
+            ambient: 0.12,
+        }
+    }
+
+    /// Luz (0..1) en un punto a `dist` px del jugador y `angle_off` rad fuera del eje del cono.
+    pub fn light_at(&self, angle_off: f32, dist: f32) -> f32 {
+        let off = angle_off.abs();
+        let inner = (self.half_angle - self.softness).max(0.0);
+        let cone = if off <= inner {
+            1.0
+        } else if off >= self.half_angle {
+            0.0
+        } else {
+            let t = (self.half_angle - off) / (self.half_angle - inner);
+            t * t * (3.0 - 2.0 * t) // smoothstep
+        };
+
+        let r = dist.max(0.0) / self.range.max(1.0);
+        let falloff = 1.0 / (1.0 + r * r);
+
+        (self.ambient + self.intensity * cone * falloff).min(1.0)
+    }
+
+    /// Ajusta el cono según el enemigo más cercano: lejos, ancho y largo;
+    /// cerca, estrecho, corto y casi sin luz ambiente.
+    pub fn follow_enemies(&mut self, player: &Player, enemies: &[Enemy]) {
+        // Calcula la distancia mínima del jugador a cualquier enemigo
+        let mut min_enemy_dist = f32::INFINITY;
+        for enemy in enemies.iter() {
+            let dx = enemy.pos.x - player.pos.x;
+            let dy = enemy.pos.y - player.pos.y;
+            let d = (dx*dx + dy*dy).sqrt();
+            if d < min_enemy_dist { min_enemy_dist = d; }
+        }
+
+        let max_effect_distance = 1500.0_f32; // comienza a afectar desde más lejos
+        let min_effect_distance = 80.0_f32;  // muy cerca = cono mínimo
+
+        // calcular factor de proximidad t en [0..1]
+        let t = if min_enemy_dist >= max_effect_distance {
+            0.0_f32
+        } else if min_enemy_dist <= min_effect_distance {
+            1.0_f32
+        } else {
+            (max_effect_distance - min_enemy_dist) / (max_effect_distance - min_effect_distance)
+        };
+
+        let max_half_angle = player.fov * 0.5;
+        let min_half_angle = PI / 30.0;
+        self.half_angle = max_half_angle * (1.0 - t) + min_half_angle * t;
+        self.softness = self.half_angle * 0.35;
+        self.range = 600.0 * (1.0 - t) + 150.0 * t;
+        self.ambient = 0.12 * (1.0 - t) + 0.02 * t;
+    }
+
+    /// Igual que `light_at`, pero con el desvío separado en horizontal (columna) y vertical (fila).
+    pub fn light_at_screen(&self, h_off: f32, v_off: f32, dist: f32) -> f32 {
+        self.light_at((h_off * h_off + v_off * v_off).sqrt(), dist)
+    }
+}
+
+/// Escala el color por la luz recibida (0..1), respetando el alpha.
+pub fn shade(color: Color, light: f32) -> Color {
+    let l = light.clamp(0.0, 1.0);
+    Color::new(
+        (color.r as f32 * l) as u8,
+        (color.g as f32 * l) as u8,
+        (color.b as f32 * l) as u8,
+        color.a,
+    )
+}
+
+/// Combina la linterna (escalar) con la luz de color de las lámparas.
+pub fn shade_lit(color: Color, flash: f32, point: [f32; 3]) -> Color {
+    Color::new(
+        (color.r as f32 * (flash + point[0]).clamp(0.0, 1.0)) as u8,
+        (color.g as f32 * (flash + point[1]).clamp(0.0, 1.0)) as u8,
+        (color.b as f32 * (flash + point[2]).clamp(0.0, 1.0)) as u8,
+        color.a,
+    )
+}
+
+/// Animación de una luz puntual.
+#[derive(Clone, Copy, PartialEq)]
+pub enum LightMode {
+    Steady,
+    Flicker,
+    Strobe,
+    Broken, // lámpara rota: se corta a menudo y suelta chispas al cortarse
+}
+
+/// Luz puntual colocada en el mapa (lámpara de techo, luz de emergencia...).
+pub struct PointLight {
+    pub pos: Vector2,
+    pub radius: f32,
+    pub color: Color,
+    pub mode: LightMode,
+    pub phase: f32, // desfase para que dos luces iguales no parpadeen a la vez
+    level: f32,     // intensidad animada actual (0..1)
+}
+
+impl PointLight {
+    /// Intensidad actual (0..1) tras el parpadeo y el apagón.
+    pub fn level(&self) -> f32 {
+        self.level
+    }
+}
+
+/// Apagón programado: cuando Jack aparece (a la vista y a menos de `distance`),
+/// las luces del piso se apagan durante `duration` segundos (0 = hasta cambiar de piso).
+/// Al volver la luz se rearma: puede repetirse cuando Jack se pierda de vista y reaparezca.
+struct Blackout {
+    distance: f32,
+    duration: f32,
+}
+
+// Tamaño (px del mundo) de las muestras del mapa de visibilidad; divide a block_size
+const SAMPLE_SIZE: usize = 20;
+// Duración del parpadeo antes de quedar a oscuras
+const BLACKOUT_FADE: f32 = 0.6;
+
+/// Luces de un piso con su visibilidad precalculada por raycasting.
+pub struct LightSet {
+    pub lights: Vec<PointLight>,
+    // por cada muestra del mapa: bit i = la luz i ve esa muestra
+    visibility: Vec<u64>,
+    grid_w: usize,
+    grid_h: usize,
+    blackout: Option<Blackout>,
+    blackout_elapsed: Option<f32>, // None = no hay apagón en curso
+    blackout_armed: bool,          // false tras un apagón, hasta que Jack deje de verse
+}
+
+impl LightSet {
+    pub fn empty() -> Self {
+        LightSet {
+            lights: Vec::new(),
+            visibility: Vec::new(),
+            grid_w: 0,
+            grid_h: 0,
+            blackout: None,
+            blackout_elapsed: None,
+            blackout_armed: true,
+        }
+    }
+
+    /// Carga las luces de `filename` (si no existe, el piso queda sin luces).
+    /// Formato por línea (coordenadas en celdas, '#' = comentario):
+    ///   light <i> <j> <radio_px> <r> <g> <b> <steady|flicker|strobe|broken>
+    ///   blackout <distancia_px> <segundos>
+    pub fn load(filename: &str, maze: &Maze, block_size: usize) -> Self {
+        let mut set = LightSet::empty();
+
+        let Ok(text) = fs::read_to_string(filename) else {
+            return set;
+        };
+
+        for (n, line) in text.lines().enumerate() {
+            let line = line.split('#').next().unwrap_or("").trim();
+            if line.is_empty() {
+                continue;
+            }
+            let parts: Vec<&str> = line.split_whitespace().collect();
+            match parts.as_slice() {
+                ["light", i, j, radius, r, g, b, mode] => {
+                    let parsed = (|| {
+                        let i: f32 = i.parse().ok()?;
+                        let j: f32 = j.parse().ok()?;
+                        let mode = match *mode {
+                            "steady" => LightMode::Steady,
+                            "flicker" => LightMode::Flicker,
+                            "strobe" => LightMode::Strobe,
+                            "broken" => LightMode::Broken,
+                            _ => return None,
+                        };
+                        Some(PointLight {
+                            pos: Vector2::new(
+                                (i + 0.5) * block_size as f32,
+                                (j + 0.5) * block_size as f32,
+                            ),
+                            radius: radius.parse().ok()?,
+                            color: Color::new(r.parse().ok()?, g.parse().ok()?, b.parse().ok()?, 255),
+                            mode,
+                            phase: set.lights.len() as f32 * 1.618,
+                            level: 1.0,
+                        })
+                    })();
+                    match parsed {
+                        Some(light) if set.lights.len() < 64 => set.lights.push(light),
+                        Some(_) => eprintln!("{}:{}: máximo 64 luces por piso", filename, n + 1),
+                        None => eprintln!("{}:{}: luz inválida: {}", filename, n + 1, line),
+                    }
+                }
+                ["blackout", distance, duration] => {
+                    match (distance.parse(), duration.parse()) {
+                        (Ok(distance), Ok(duration)) => {
+                            set.blackout = Some(Blackout { distance, duration });
+                        }
+                        _ => eprintln!("{}:{}: apagón inválido: {}", filename, n + 1, line),
+                    }
+                }
+                _ => eprintln!("{}:{}: línea desconocida: {}", filename, n + 1, line),
+            }
+        }
+
+        set.compute_visibility(maze, block_size);
+        set
+    }
+
+    /// Precalcula qué muestras del mapa ve cada luz (oclusión con `cast_ray_from`).
+    fn compute_visibility(&mut self, maze: &Maze, block_size: usize) {
+        let maze_h = maze.len();
+        let maze_w = if maze_h > 0 { maze[0].len() } else { 0 };
+        self.grid_w = maze_w * block_size / SAMPLE_SIZE;
+        self.grid_h = maze_h * block_size / SAMPLE_SIZE;
+        self.visibility = vec![0; self.grid_w * self.grid_h];
+
+        for (li, light) in self.lights.iter().enumerate() {
+            let r_samples = (light.radius / SAMPLE_SIZE as f32).ceil() as isize + 1;
+            let lx = (light.pos.x / SAMPLE_SIZE as f32) as isize;
+            let ly = (light.pos.y / SAMPLE_SIZE as f32) as isize;
+
+            for sy in (ly - r_samples)..=(ly + r_samples) {
+                for sx in (lx - r_samples)..=(lx + r_samples) {
+                    if sx < 0 || sy < 0 || sx as usize >= self.grid_w || sy as usize >= self.grid_h {
+                        continue;
+                    }
+                    let cx = (sx as f32 + 0.5) * SAMPLE_SIZE as f32;
+                    let cy = (sy as f32 + 0.5) * SAMPLE_SIZE as f32;
+
+                    // solo muestras en celdas transitables
+                    let ci = cx as usize / block_size;
+                    let cj = cy as usize / block_size;
+                    let walkable = maze
+                        .get(cj)
+                        .and_then(|row| row.get(ci))
+                        .map_or(false, |&c| c == ' ' || c == 'g' || c == 's');
+                    if !walkable {
+                        continue;
+                    }
+
+                    let dx = cx - light.pos.x;
+                    let dy = cy - light.pos.y;
+                    let dist = (dx * dx + dy * dy).sqrt();
+                    if dist > light.radius + SAMPLE_SIZE as f32 {
+                        continue;
+                    }
+
+                    let visible = dist < 1.0 || {
+                        let hit = cast_ray_from(maze, light.pos, dy.atan2(dx), block_size, true);
+                        hit.distance + 1.0 >= dist
+                    };
+                    if visible {
+                        self.visibility[sy as usize * self.grid_w + sx as usize] |= 1u64 << li;
+                    }
+                }
+            }
+        }
+    }
+
+    /// Anima las luces y dispara el apagón si Jack aparece. `time` = reloj global (s).
+    pub fn update(&mut self, time: f32, dt: f32, player: &Player, enemies: &[Enemy], maze: &Maze, block_size: usize) {
+        // ¿empieza el apagón? (o, si ya pasó uno, ¿se rearma?)
+        if let (Some(blackout), None) = (&self.blackout, self.blackout_elapsed) {
+            let seen = enemies.iter().any(|e| {
+                let dx = e.pos.x - player.pos.x;
+                let dy = e.pos.y - player.pos.y;
+                let dist = (dx * dx + dy * dy).sqrt();
+                dist <= blackout.distance
+                    && cast_ray_from(maze, player.pos, dy.atan2(dx), block_size, true).distance + 1.0 >= dist
+            });
+            if seen && self.blackout_armed {
+                self.blackout_elapsed = Some(0.0);
+            } else if !seen {
+                self.blackout_armed = true;
+            }
+        }
+
+        // factor global del apagón: parpadeo corto y luego oscuridad
+        let mut blackout_factor = 1.0;
+        if let (Some(blackout), Some(elapsed)) = (&self.blackout, self.blackout_elapsed.as_mut()) {
+            *elapsed += dt;
+            let t = *elapsed;
+            blackout_factor = if t < BLACKOUT_FADE {
+                if flicker_noise(t * 30.0) > 0.5 { 1.0 } else { 0.0 }
+            } else if blackout.duration <= 0.0 || t < BLACKOUT_FADE + blackout.duration {
+                0.0
+            } else {
+                // vuelve la luz; el siguiente apagón espera a que Jack se pierda de vista
+                self.blackout_elapsed = None;
+                self.blackout_armed = false;
+                1.0
+            };
+        }
+
+        for light in self.lights.iter_mut() {
+            let anim = match light.mode {
+                LightMode::Steady => 1.0,
+                LightMode::Flicker => {
+                    let n = flicker_noise(time * 12.0 + light.phase * 7.0);
+                    // casi siempre encendida, con caídas bruscas ocasionales
+                    if n < 0.08 { 0.05 } else { 0.75 + 0.25 * n }
+                }
+                LightMode::Strobe => {
+                    if (time * 3.0 + light.phase).fract() < 0.5 { 1.0 } else { 0.0 }
+                }
+                LightMode::Broken => {
+                    let n = flicker_noise(time * 9.0 + light.phase * 7.0);
+                    // se corta del todo un buen rato de cada tanto, y cuando prende zumba
+                    if n < 0.3 { 0.0 } else { 0.5 + 0.4 * n }
+                }
+            };
+            light.level = anim * blackout_factor;
+        }
+    }
+
+    /// Lámparas rotas que están cortadas por su propio fallo (no por un apagón): las que echan chispas.
+    pub fn sparking(&self) -> impl Iterator<Item = &PointLight> + '_ {
+        let blackout = self.blackout_elapsed.is_some();
+        self.lights.iter().filter(move |l| !blackout && l.mode == LightMode::Broken && l.level < 0.1)
+    }
+
+    /// Luz de color (por canal, 0..) que reciben las lámparas en el punto (x, y) del mundo.
+    pub fn light_at(&self, x: f32, y: f32) -> [f32; 3] {
+        let mut out = [0.0; 3];
+        if self.lights.is_empty() || x < 0.0 || y < 0.0 {
+            return out;
+        }
+        let sx = x as usize / SAMPLE_SIZE;
+        let sy = y as usize / SAMPLE_SIZE;
+        if sx >= self.grid_w || sy >= self.grid_h {
+            return out;
+        }
+        let mask = self.visibility[sy * self.grid_w + sx];
+        if mask == 0 {
+            return out;
+        }
+
+        for (li, light) in self.lights.iter().enumerate() {
+            if mask & (1u64 << li) == 0 || light.level <= 0.0 {
+                continue;
+            }
+            let dx = x - light.pos.x;
+            let dy = y - light.pos.y;
+            let dist = (dx * dx + dy * dy).sqrt();
+            if dist >= light.radius {
+                continue;
+            }
+            let f = 1.0 - dist / light.radius;
+            let k = f * f * light.level / 255.0;
+            out[0] += light.color.r as f32 * k;
+            out[1] += light.color.g as f32 * k;
+            out[2] += light.color.b as f32 * k;
+        }
+        out
+    }
+}
+
+/// Ruido determinista en [0, 1) a partir de un valor continuo (escalonado por enteros).
+fn flicker_noise(t: f32) -> f32 {
+    let mut h = (t.floor() as i32 as u32).wrapping_mul(0x9E37_79B9);
+    h ^= h >> 15;
+    h = h.wrapping_mul(0x85EB_CA6B);
+    h ^= h >> 13;
+    (h & 0xFFFF) as f32 / 65536.0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Pasillo recto de 8 celdas con un muro alrededor.
+    fn corridor() -> Maze {
+        vec!["+--------+", "|        |", "+--------+"].iter().map(|r| r.chars().collect()).collect()
+    }
+
+    fn lamp() -> PointLight {
+        PointLight {
+            pos: Vector2::new(150.0, 150.0),
+            radius: 300.0,
+            color: Color::WHITE,
+            mode: LightMode::Steady,
+            phase: 0.0,
+            level: 1.0,
+        }
+    }
+
+    fn close(a: f32, b: f32) -> bool {
+        (a - b).abs() < 1e-4
+    }
+
+    #[test]
+    fn cone_is_full_inside_ambient_outside() {
+        let f = Flashlight::new();
+        assert!(close(f.light_at(0.0, 0.0), 1.0));
+        assert!(close(f.light_at(f.half_angle + 0.01, 0.0), f.ambient));
+        assert!(close(f.light_at(-(f.half_angle + 0.01), 100.0), f.ambient));
+        // a `range` de distancia la luz del cono cae a la mitad
+        assert!(close(f.light_at(0.0, f.range), f.ambient + f.intensity * 0.5));
+    }
+
+    #[test]
+    fn cone_falls_off_with_angle_and_distance() {
+        let f = Flashlight::new();
+        let inner = f.half_angle - f.softness;
+        let mut last = f.light_at(inner, 200.0);
+        for i in 1..=20 {
+            let light = f.light_at(inner + f.softness * i as f32 / 20.0, 200.0);
+            assert!(light <= last, "el borde suave no debe subir");
+            last = light;
+        }
+        // de cerca se satura en 1; después baja sin llegar nunca al ambiente
+        let mut last = f.light_at(0.0, 0.0);
+        for d in (100..3000).step_by(100) {
+            let light = f.light_at(0.0, d as f32);
+            assert!(light <= last && light > f.ambient);
+            last = light;
+        }
+        assert!(last < 0.2);
+        // horizontal y vertical cuentan igual
+        assert!(close(f.light_at_screen(0.3, 0.4, 50.0), f.light_at(0.5, 50.0)));
+    }
+
+    #[test]
+    fn cone_narrows_when_jack_is_close() {
+        let player = Player { pos: Vector2::new(0.0, 0.0), a: 0.0, fov: PI / 2.0 };
+        let mut far = Flashlight::new();
+        far.follow_enemies(&player, &[Enemy::new(2000.0, 0.0, vec!['e'], 1)]);
+        let mut near = Flashlight::new();
+        near.follow_enemies(&player, &[Enemy::new(50.0, 0.0, vec!['e'], 1)]);
+        assert!(close(far.half_angle, player.fov * 0.5));
+        assert!(close(near.half_angle, PI / 30.0));
+        assert!(near.range < far.range && near.ambient < far.ambient);
+    }
+
+    #[test]
+    fn shade_lit_adds_lamps_and_keeps_alpha() {
+        let c = Color::new(200, 100, 50, 77);
+        assert_eq!(shade_lit(c, 0.5, [0.0; 3]), Color::new(100, 50, 25, 77));
+        assert_eq!(shade_lit(c, 0.5, [0.5, 0.0, 2.0]), Color::new(200, 50, 50, 77));
+        assert_eq!(shade(c, 0.0), Color::new(0, 0, 0, 77));
+    }
+
+    #[test]
+    fn blackout_rearms_after_jack_leaves() {
+        let maze = corridor();
+        let mut set = LightSet::empty();
+        set.lights.push(lamp());
+        set.blackout = Some(Blackout { distance: 500.0, duration: 1.0 });
+        let player = Player { pos: Vector2::new(150.0, 150.0), a: 0.0, fov: PI / 2.0 };
+        let near = [Enemy::new(450.0, 150.0, vec!['e'], 1)];
+        let far = [Enemy::new(850.0, 150.0, vec!['e'], 1)];
+        let step = |set: &mut LightSet, enemies: &[Enemy], seconds: f32| {
+            for _ in 0..(seconds * 10.0) as usize {
+                set.update(0.0, 0.1, &player, enemies, &maze, 100);
+            }
+        };
+
+        step(&mut set, &near, BLACKOUT_FADE + 0.5);
+        assert_eq!(set.lights[0].level(), 0.0, "Jack a la vista: apagón");
+        step(&mut set, &near, 1.0);
+        assert_eq!(set.lights[0].level(), 1.0, "pasado el apagón vuelve la luz");
+        step(&mut set, &near, 2.0);
+        assert_eq!(set.lights[0].level(), 1.0, "sin perderlo de vista no se repite");
+        step(&mut set, &far, 0.2);
+        step(&mut set, &near, BLACKOUT_FADE + 0.5);
+        assert_eq!(set.lights[0].level(), 0.0, "al reaparecer hay otro apagón");
+    }
+
+    #[test]
+    fn only_broken_lamps_spark_and_not_in_a_blackout() {
+        let maze = corridor();
+        let mut set = LightSet::empty();
+        set.lights.push(PointLight { mode: LightMode::Flicker, ..lamp() });
+        set.lights.push(PointLight { mode: LightMode::Broken, pos: Vector2::new(650.0, 150.0), ..lamp() });
+        set.blackout = Some(Blackout { distance: 500.0, duration: 0.0 });
+        let player = Player { pos: Vector2::new(150.0, 150.0), a: 0.0, fov: PI / 2.0 };
+        let near = [Enemy::new(450.0, 150.0, vec!['e'], 1)];
+
+        let mut sparked = 0;
+        for step in 0..200 {
+            set.update(step as f32 * 0.05, 0.05, &player, &[], &maze, 100);
+            let sparking: Vec<Vector2> = set.sparking().map(|l| l.pos).collect();
+            assert!(sparking.iter().all(|p| p.x == 650.0), "la lámpara que parpadea no está rota");
+            sparked += sparking.len();
+        }
+        assert!(sparked > 0, "la lámpara rota se corta alguna vez");
+
+        // en el apagón todas quedan a oscuras, pero eso no las rompe
+        for step in 0..200 {
+            set.update(step as f32 * 0.05, 0.05, &player, &near, &maze, 100);
+            assert_eq!(set.sparking().count(), 0);
+        }
+        assert!(set.lights.iter().all(|l| l.level() == 0.0));
+    }
+}
@@ -7,22 +7,54 @@ mod player;
 mod caster;
 mod textures;
 mod enemy;
+mod light;
+mod settings;
+mod decals;
+mod resolution;
+mod headless;
+mod present;
+mod terminal;
+mod font;
+mod postfx;
+mod capture;
+mod gif;
+mod hud;
+mod procgen;
+mod palette;
+mod sprite;
+mod particles;
+mod game;
 
 use raylib::prelude::*;
 use std::ffi::CString;
 use raylib::ffi;
 use std::thread;
-use std::time::Duration;
+use std::time::{Duration, Instant};
 use std::collections::VecDeque;
-use framebuffer::Framebuffer;
+use framebuffer::{Framebuffer, blend_over};
+use font::Align;
 use maze::{Maze,load_maze};
 use player::Player;
 use std::f32::consts::PI;
-use textures::TextureManager;
+use textures::{TextureManager, ALPHA_CUTOFF};
 use enemy::Enemy;
-
-use crate::{caster::cast_ray, player::process_events};
-
+use light::{Flashlight, LightSet, shade_lit};
+use palette::light_level;
+use sprite::{render_sprites, Sprite, SpriteLimits, VerticalAnchor};
+use particles::Particles;
+use game::{Event, Game};
+use settings::Settings;
+use decals::Decals;
+use resolution::RenderScale;
+use postfx::{Effect, PostFx};
+use capture::Capture;
+use hud::{Hud, HudState, Widget};
+use present::RaylibPresenter;
+
+use crate::{caster::{cast_ray, cast_ray_from}, player::process_events};
+
+// Pisos del juego, en orden
+const LEVELS: &[i32] = &[3, 4, 5, 6, 7];
 
 fn maze_filename_for_level(level: i32) -> &'static str {
     // Alterna entre maze_odd y maze_even excepto el nivel final 7
@@ -35,6 +67,16 @@ fn maze_filename_for_level(level: i32) -> &'static str {
     }
 }
 
+/// Archivo de luces que acompaña al laberinto de un nivel (p.ej. maze_odd.lights).
+fn lights_filename_for_level(level: i32) -> String {
+    maze_filename_for_level(level).replace(".txt", ".lights")
+}
+
+/// Archivo de calcomanías / texturas por cara del laberinto de un nivel (p.ej. maze_odd.decals).
+fn decals_filename_for_level(level: i32) -> String {
+    maze_filename_for_level(level).replace(".txt", ".decals")
+}
+
 fn draw_cell(
     framebuffer: &mut Framebuffer,
     xo: usize,
@@ -46,13 +88,7 @@ fn draw_cell(
         return;
     }
 
-    framebuffer.set_current_color(Color::RED);
-
-    for x in xo..xo + block_size {
-        for y in yo..yo + block_size {
-            framebuffer.set_pixel(x as i32, y as i32);
-        }
-    }
+    framebuffer.fill_rect(xo as i32, yo as i32, block_size as i32, block_size as i32, Color::RED);
 }
 
 pub fn render_maze(
@@ -97,30 +133,20 @@ pub fn render_maze(
             let cell_h = (block_size as f32) * scale;
 
             // color de pared (ajusta si quieres)
-            framebuffer.set_current_color(Color::new(200, 40, 40, 255));
             let ix0 = cell_x.floor() as i32;
             let iy0 = cell_y.floor() as i32;
             let ix1 = (cell_x + cell_w).ceil() as i32;
             let iy1 = (cell_y + cell_h).ceil() as i32;
-            for px in ix0..ix1 {
-                for py in iy0..iy1 {
-                    framebuffer.set_pixel(px, py);
-                }
-            }
+            framebuffer.fill_rect(ix0, iy0, ix1 - ix0, iy1 - iy0, Color::new(200, 40, 40, 255));
         }
     }
 
     // Dibujar jugador (posición escalada)
     let player_sx = offset_x + player.pos.x * scale;
     let player_sy = offset_y + player.pos.y * scale;
-    framebuffer.set_current_color(Color::WHITE);
     let pxi = player_sx as i32;
     let pyi = player_sy as i32;
-    for dx in -2..=2 {
-        for dy in -2..=2 {
-            framebuffer.set_pixel(pxi + dx, pyi + dy);
-        }
-    }
+    framebuffer.fill_rect(pxi - 2, pyi - 2, 5, 5, Color::WHITE);
 
     // Dibujar rayos (debug) escalados — usamos cast_ray con draw_line=false y calculamos punta
     let num_rays = 32usize.min(16); // ajustable
@@ -139,19 +165,30 @@ pub fn render_maze(
         let sx = offset_x + ix_world * scale;
         let sy = offset_y + iy_world * scale;
 
-        // dibujar línea desde jugador hasta impacto (muestreo simple)
-        let steps = ((d * scale).max(4.0)) as usize;
-        framebuffer.set_current_color(Color::new(255, 255, 255, 255));
-        for s in 0..=steps {
-            let t = s as f32 / (steps as f32).max(1.0);
-            let lx = player_sx + (sx - player_sx) * t;
-            let ly = player_sy + (sy - player_sy) * t;
-            framebuffer.set_pixel(lx as i32, ly as i32);
-        }
+        // dibujar línea desde jugador hasta impacto
+        framebuffer.draw_line(pxi, pyi, sx as i32, sy as i32, Color::WHITE);
     }
 }
 
 
+/// Todo lo que necesita un hilo para dibujar columnas de la vista 3D (solo lectura).
+struct ColumnContext<'a> {
+    player: &'a Player,
+    maze: &'a Maze,
+    texture_cache: &'a TextureManager,
+    current_level: usize,
+    flashlight: &'a Flashlight,
+    lights: &'a LightSet,
+    decals: &'a Decals,
+    time: f32,
+    width: usize,
+    height: usize,
+}
+
+/// Dibuja la vista 3D. Cada columna (un rayo: techo, pared y suelo) es independiente, así que
+/// se reparten en grupos de columnas entre `threads` hilos, cada uno escribiendo su parte del
+/// buffer por columnas del framebuffer, que al final se copia al frame una sola vez.
+/// El resultado es idéntico al de un solo hilo.
 pub fn render_world(
     framebuffer: &mut Framebuffer,
     player: &Player,
@@ -159,203 +196,261 @@ pub fn render_world(
     texture_cache: &TextureManager,
     depth_buffer: &mut [f32],
     current_level: usize,
+    flashlight: &Flashlight,
+    lights: &LightSet,
+    decals: &Decals,
+    time: f32,
+    threads: usize,
 ) {
-    let block_size = 100;
-    let num_rays = framebuffer.width as usize;
-
-    let hh = framebuffer.height as f32 / 2.0;
-
-    //techo
-    let ceiling_tex_key = if current_level == 7 { 'k' } else { 'c' }; 
-    let tex_width = 578.0;
-    let tex_height = 347.0;
-    for y in 0..hh as usize {
-        for x in 0..framebuffer.width as usize {
-            let tx = (x as f32 / framebuffer.width as f32) * tex_width;
-            let ty = (y as f32 / hh) * tex_height;
-
-            let color = texture_cache.get_pixel_color(ceiling_tex_key, tx as u32, ty as u32);
-            framebuffer.set_current_color(color);
-            framebuffer.set_pixel(x as i32, y as i32);
-        }
+    let width = framebuffer.width as usize;
+    let height = framebuffer.height as usize;
+    if width == 0 || height == 0 {
+        return;
     }
 
-    let mut min_stake_top = framebuffer.height as usize; 
-    for i in 0..num_rays {
-        let current_ray = i as f32 / num_rays as f32;
-        let a = player.a - (player.fov / 2.0) + (player.fov * current_ray);
-        let intersect = cast_ray(framebuffer, maze, player, a, block_size, false, current_level == 7);
-        depth_buffer[i] = intersect.distance;
+    let ctx = ColumnContext {
+        player,
+        maze,
+        texture_cache,
+        current_level,
+        flashlight,
+        lights,
+        decals,
+        time,
+        width,
+        height,
+    };
 
-        let angle_diff = a - player.a;
-        let mut distance_to_wall = intersect.distance * angle_diff.cos();
-        if distance_to_wall < 0.1 {
-            distance_to_wall = 0.2;
-        }
+    // columna x = columns[x * height .. (x + 1) * height]; render_column escribe cada píxel
+    let columns = framebuffer.columns_mut();
+    let threads = threads.clamp(1, width);
+    let cols_per_thread = width.div_ceil(threads);
 
-        // Altura de la pared
-        let stake_height = (hh / distance_to_wall) * 70.0;
-        let stake_top = (hh - (stake_height / 2.0)).max(0.0) as usize;
-        let stake_bottom = (hh + (stake_height / 2.0)).min(framebuffer.height as f32) as usize;
-        if stake_top < min_stake_top {min_stake_top = stake_top;}
-
-        // --- Pared ---
-        for y in stake_top..stake_bottom {
-            let tx = intersect.tx;
-            let ty = (y as f32 - stake_top as f32)
-                / (stake_bottom as f32 - stake_top as f32)
-                * 128.0;
-
-            let impact = intersect.impact;
-            let tex_key = if impact == 'L' {
-                std::char::from_digit(current_level as u32, 10).unwrap_or('L')
-            } else {
-                impact
-            };
-            let color = texture_cache.get_pixel_color(tex_key, tx as u32, ty as u32);
-            framebuffer.set_current_color(color);
-            framebuffer.set_pixel(i as i32, y as i32);
+    if threads == 1 {
+        for (i, (column, depth)) in columns.chunks_mut(height).zip(depth_buffer.iter_mut()).enumerate() {
+            render_column(&ctx, i, column, depth);
         }
+    } else {
+        thread::scope(|scope| {
+            let groups = columns
+                .chunks_mut(cols_per_thread * height)
+                .zip(depth_buffer.chunks_mut(cols_per_thread));
+            for (g, (pixels, depths)) in groups.enumerate() {
+                let ctx = &ctx;
+                scope.spawn(move || {
+                    for (k, (column, depth)) in pixels.chunks_mut(height).zip(depths.iter_mut()).enumerate() {
+                        render_column(ctx, g * cols_per_thread + k, column, depth);
+                    }
+                });
+            }
+        });
+    }
 
-        // --- Suelo (optimizado) ---
-        let floor_tex_key = 'f';
-        let cos_a = a.cos();
-        let sin_a = a.sin();
-        let cos_angle_diff = angle_diff.cos();
-
-        // Saltamos filas para reducir carga: cada 5 píxeles (si no no llega a 60fps xd)
-        let step = 5;
+    framebuffer.commit_columns();
+}
 
-        for y in (stake_bottom..framebuffer.height as usize).step_by(step) {
-            let perspective = hh / (y as f32 - hh);
-            let dist = perspective / cos_angle_diff;
+/// Dibuja la columna `i` de la vista 3D (techo, pared y suelo) en `column` (una fila por píxel)
+/// y deja en `depth` la distancia perpendicular (sin ojo de pez) de la pared, la misma medida
+/// que usan los sprites.
+fn render_column(ctx: &ColumnContext, i: usize, column: &mut [Color], depth: &mut f32) {
+    let player = ctx.player;
+    let texture_cache = ctx.texture_cache;
+    let flashlight = ctx.flashlight;
+    let lights = ctx.lights;
+    let time = ctx.time;
+    let block_size = 100;
+    let num_rays = ctx.width;
 
-            let floor_x = player.pos.x + dist * cos_a;
-            let floor_y = player.pos.y + dist * sin_a;
+    let hh = ctx.height as f32 / 2.0;
+    // radianes por píxel de pantalla (mismo paso en horizontal y vertical para la linterna)
+    let ang_per_px = player.fov / ctx.width as f32;
 
-            let tx = ((floor_x as usize % block_size) as f32 / block_size as f32) * 200.0;
-            let ty = ((floor_y as usize % block_size) as f32 / block_size as f32) * 150.0;
+    //techo
+    let ceiling_tex_key = if ctx.current_level == 7 { 'k' } else { 'c' }; 
+    let tex_width = 578.0;
+    let tex_height = 347.0;
+    // el techo se mapea en espacio de pantalla: un píxel cubre siempre los mismos texeles
+    let ceiling_tpp = (tex_width / ctx.width as f32).max(tex_height / hh);
+    // dirección de la columna (para ubicar en el mundo el punto del techo)
+    let h_off = (i as f32 - ctx.width as f32 / 2.0) * ang_per_px;
+    let (dir_x, dir_y, cos_off) = {
+        let a = player.a + h_off;
+        (a.cos(), a.sin(), h_off.cos())
+    };
+    for y in 0..hh as usize {
+        let tx = (i as f32 / ctx.width as f32) * tex_width;
+        let ty = (y as f32 / hh) * tex_height;
 
-            let color = texture_cache.get_pixel_color(floor_tex_key, tx as u32, ty as u32);
-            framebuffer.set_current_color(color);
+        // distancia aproximada del punto del techo (misma proyección que las paredes)
+        let dist = 35.0 * hh / (hh - y as f32).max(1.0);
+        let v_off = (y as f32 - hh) * ang_per_px;
+        let light = flashlight.light_at_screen(h_off, v_off, dist);
+        let ray_dist = dist / cos_off;
+        let lamp = lights.light_at(player.pos.x + ray_dist * dir_x, player.pos.y + ray_dist * dir_y);
 
-            // Rellenar los píxeles faltantes entre pasos
-            for dy in 0..step {
-                if y + dy < framebuffer.height as usize {
-                    framebuffer.set_pixel(i as i32, (y + dy) as i32);
-                }
-            }
-        }
+        column[y] = lit_texel(texture_cache, ceiling_tex_key, time, tx, ty, ceiling_tpp, light, lamp);
     }
-}
 
+    let current_ray = i as f32 / num_rays as f32;
+    let a = player.a - (player.fov / 2.0) + (player.fov * current_ray);
+    let intersect = cast_ray_from(ctx.maze, player.pos, a, block_size, ctx.current_level == 7);
 
-const TRANSPARENT_COLOR: Color = Color::new(152, 0, 136, 255);
-fn draw_sprite(
-    framebuffer: &mut Framebuffer,
-    player: &Player,
-    enemy: &Enemy,
-    texture_manager: &TextureManager,
-    depth_buffer: &[f32]
-) {
-    let sprite_a = (enemy.pos.y - player.pos.y).atan2(enemy.pos.x - player.pos.x);
-    let mut angle_diff = sprite_a - player.a;
-    while angle_diff > PI {
-        angle_diff -= 2.0 * PI;
-    }
-    while angle_diff < -PI {
-        angle_diff += 2.0 * PI;
+    let angle_diff = a - player.a;
+    let mut distance_to_wall = intersect.distance * angle_diff.cos();
+    if distance_to_wall < 0.1 {
+        distance_to_wall = 0.2;
     }
+    *depth = distance_to_wall;
+
+    // Altura de la pared
+    let stake_height = (hh / distance_to_wall) * 70.0;
+    let stake_top = (hh - (stake_height / 2.0)).max(0.0) as usize;
+    let stake_bottom = (hh + (stake_height / 2.0)).min(ctx.height as f32) as usize;
+
+    // luz de las lámparas en el punto de impacto (retrocedido un poco hacia el jugador,
+    // para muestrear el lado transitable de la pared)
+    let hit_d = (intersect.distance - 2.0).max(0.0);
+    let wall_lamp = lights.light_at(player.pos.x + hit_d * a.cos(), player.pos.y + hit_d * a.sin());
+
+    // texeles por píxel en vertical (128 texeles a lo alto de la pared) -> nivel de mipmap
+    let wall_tpp = 128.0 / stake_height;
+
+    // textura de la cara: la propia de la cara si el mapa la define, si no la del carácter
+    let face_art = ctx.decals.at(intersect.cell, intersect.face);
+    let impact = intersect.impact;
+    let tex_key = if let Some(base) = face_art.and_then(|art| art.base) {
+        base
+    } else if impact == 'L' {
+        std::char::from_digit(ctx.current_level as u32, 10).unwrap_or('L')
+    } else {
+        impact
+    };
+    let face_u = decals::face_u(intersect.tx, intersect.face);
+    // calcomanías que cubren la altura v (0..1) de la cara: (clave, x, y, texeles por píxel)
+    let decals_at = |v: f32| {
+        face_art
+            .into_iter()
+            .flat_map(|art| art.decals.iter())
+            .filter(move |d| face_u >= d.u0 && face_u < d.u1 && v >= d.v0 && v < d.v1)
+            .map(move |decal| {
+                let (dw, dh) = texture_cache.size(decal.key).unwrap_or((1, 1));
+                let dx = (face_u - decal.u0) / (decal.u1 - decal.u0) * dw as f32;
+                let dy = (v - decal.v0) / (decal.v1 - decal.v0) * dh as f32;
+                let decal_tpp = dh as f32 / ((decal.v1 - decal.v0) * stake_height);
+                (decal.key, dx, dy, decal_tpp)
+            })
+    };
 
-    // fuera del FOV
-    if angle_diff.abs() > player.fov / 2.5 {
-        return;
+    // --- Pared ---
+    for y in stake_top..stake_bottom {
+        let tx = intersect.tx;
+        let ty = (y as f32 - stake_top as f32)
+            / (stake_bottom as f32 - stake_top as f32)
+            * 128.0;
+
+        let light = flashlight.light_at_screen(angle_diff, (y as f32 - hh) * ang_per_px, distance_to_wall);
+
+        // calcomanías encima de la textura base (en orden de aparición en el archivo)
+        column[y] = match texture_cache.palette() {
+            Some(palette) => {
+                // con paleta no hay mezcla: la calcomanía tapa o no tapa
+                let mut index = texture_cache.sample_index_at(tex_key, time, tx as f32, ty, wall_tpp).0;
+                for (key, dx, dy, decal_tpp) in decals_at(ty / 128.0) {
+                    let (decal_index, alpha) = texture_cache.sample_index_at(key, time, dx, dy, decal_tpp);
+                    if alpha >= ALPHA_CUTOFF {
+                        index = decal_index;
+                    }
+                }
+                palette.shade(index, light_level(light, wall_lamp))
+            }
+            None => {
+                let mut color = texture_cache.sample_at(tex_key, time, tx as f32, ty, wall_tpp);
+                for (key, dx, dy, decal_tpp) in decals_at(ty / 128.0) {
+                    color = blend_over(color, texture_cache.sample_at(key, time, dx, dy, decal_tpp));
+                }
+                shade_lit(color, light, wall_lamp)
+            }
+        };
     }
 
-    let sprite_d: f32 = ((player.pos.x - enemy.pos.x).powi(2) + (player.pos.y - enemy.pos.y).powi(2)).sqrt();
+    // --- Suelo (optimizado) ---
+    let floor_tex_key = 'f';
+    let cos_a = a.cos();
+    let sin_a = a.sin();
+    let cos_angle_diff = angle_diff.cos();
 
-    // near plane / far plane
-    if sprite_d < 50.0 || sprite_d > 1000.0 {
-        return;
-    }
+    // Saltamos filas para reducir carga: cada 5 píxeles (si no no llega a 60fps xd)
+    let step = 5;
 
-    let screen_height = framebuffer.height as f32;
-    let screen_width = framebuffer.width as f32;
+    for y in (stake_bottom..ctx.height).step_by(step) {
+        let perspective = hh / (y as f32 - hh);
+        let dist = perspective / cos_angle_diff;
 
-    let sprite_size = (screen_height / sprite_d) * 70.0;
-    if sprite_size < 1.0 {
-        return;
-    }
+        let floor_x = player.pos.x + dist * cos_a;
+        let floor_y = player.pos.y + dist * sin_a;
 
-    let screen_x = ((angle_diff / player.fov) + 0.5) * screen_width;
+        let tx = ((floor_x as usize % block_size) as f32 / block_size as f32) * 200.0;
+        let ty = ((floor_y as usize % block_size) as f32 / block_size as f32) * 150.0;
 
-    let start_x = (screen_x - sprite_size / 2.0).max(0.0) as usize;
-    let start_y = (screen_height / 2.0 - sprite_size / 2.0).max(0.0) as usize;
-    let sprite_size_usize: usize = sprite_size.max(1.0) as usize;
-    let end_x = (start_x + sprite_size_usize).min(framebuffer.width as usize);
-    let end_y = (start_y + sprite_size_usize).min(framebuffer.height as usize);
+        // cuánto suelo cubre este píxel: entre filas (saltando `step`) y entre columnas;
+        // la textura tiene ~2 texeles por unidad del mundo
+        let row_span = hh / ((y as f32 - hh) * (y as f32 - hh)) / cos_angle_diff * step as f32;
+        let col_span = dist * ang_per_px;
+        let floor_tpp = 2.0 * row_span.max(col_span);
 
-    // obtener tamaño real de la textura desde Texture2D si existe
-    let (tex_w, tex_h) = if let Some(tex) = texture_manager.get_texture(enemy.current_key()) {
-        (tex.width() as f32, tex.height() as f32)
-    } else {
-        // fallback si no existe la textura
-        (128.0, 128.0)
-    };
+        // distancia real del punto del suelo (misma proyección que las paredes)
+        let light_dist = 35.0 * hh / (y as f32 - hh).max(1.0);
+        let light = flashlight.light_at_screen(angle_diff, (y as f32 - hh) * ang_per_px, light_dist);
+        let ray_dist = light_dist / cos_angle_diff;
+        let lamp = lights.light_at(player.pos.x + ray_dist * cos_a, player.pos.y + ray_dist * sin_a);
 
-     for x in start_x..end_x {
-        // Ocultación por columna: si sprite está detrás de la pared en esta columna, saltamos toda la columna
-        // depth_buffer usa distancias crudas (igual que sprite_d)
-        if sprite_d >= depth_buffer[x] {
-            continue;
-        }
-
-        for y in start_y..end_y {
-            let tx_f = ((x as f32 - start_x as f32) / sprite_size) * tex_w;
-            let ty_f = ((y as f32 - start_y as f32) / sprite_size) * tex_h;
-
-            let tx_u32 = tx_f.max(0.0).min(tex_w - 1.0) as u32;
-            let ty_u32 = ty_f.max(0.0).min(tex_h - 1.0) as u32;
+        let color = lit_texel(texture_cache, floor_tex_key, time, tx, ty, floor_tpp, light, lamp);
 
-            let key = enemy.current_key();
-            let color = texture_manager.get_pixel_color(key, tx_u32, ty_u32);
-
-            if color.a == 0 {
-                continue;
+        // Rellenar los píxeles faltantes entre pasos
+        for dy in 0..step {
+            if y + dy < ctx.height {
+                column[y + dy] = color;
             }
-
-            framebuffer.set_current_color(color);
-            framebuffer.set_pixel(x as i32, y as i32);
         }
     }
 }
+/// Texel de `key` ya iluminado: con paleta, con las tablas de luz; si no, escalando el color.
+fn lit_texel(texture_cache: &TextureManager, key: char, time: f32, tx: f32, ty: f32, tpp: f32, light: f32, lamp: [f32; 3]) -> Color {
+    match texture_cache.palette() {
+        Some(palette) => palette.shade(texture_cache.sample_index_at(key, time, tx, ty, tpp).0, light_level(light, lamp)),
+        None => shade_lit(texture_cache.sample_at(key, time, tx, ty, tpp), light, lamp),
+    }
+}
 
+// Texturas generadas (ver assets/textures.txt) para la sombra de Jack y el brillo de las lámparas
+const SHADOW_KEY: char = '_';
+const LAMP_KEY: char = '*';
 
-
-fn render_enemies(
-    framebuffer: &mut Framebuffer,
-    player: &Player,
-    texture_cache: &TextureManager,
-    depth_buffer: &[f32],
-    enemies: &[Enemy],
-) {
+/// Sprites de la escena: los enemigos con su sombra, el brillo de cada lámpara del techo y,
+/// en el nivel final, la estatua en cada casilla 'g'.
+fn world_sprites(player: &Player, maze: &Maze, enemies: &[Enemy], lights: &LightSet, current_level: usize) -> Vec<Sprite> {
+    let mut sprites = Vec::new();
     for enemy in enemies {
-        draw_sprite(framebuffer, player, enemy, texture_cache, depth_buffer);
+        // la sombra primero (tendida en el suelo): a la misma distancia, el orden de dibujo respeta el de la lista
+        sprites.push(Sprite::new(enemy.pos, SHADOW_KEY).sized(60.0, VerticalAnchor::Flat).with_width(60.0));
+        sprites.push(enemy.sprite());
+    }
+    for light in &lights.lights {
+        // el brillo sigue a la lámpara: parpadea con ella y desaparece en el apagón
+        let level = light.level();
+        if level < 0.01 {
+            continue;
+        }
+        sprites.push(Sprite {
+            alpha: level,
+            emissive: true,
+            ..Sprite::new(light.pos, LAMP_KEY).sized(10.0, VerticalAnchor::Ceiling).with_width(28.0)
+        });
     }
-}
 
-/// Dibuja las casillas 'g' como billboards usando draw_sprite() (con la textura 'p').
-fn render_goal_sprites(
-    framebuffer: &mut Framebuffer,
-    player: &Player,
-    maze: &Maze,
-    texture_cache: &TextureManager,
-    depth_buffer: &[f32],
-    current_level: usize,
-) {
-    // solo en nivel final
+    // la estatua solo en el nivel final
     if current_level != 7 {
-        return;
+        return sprites;
     }
 
     let block_size = 100usize;
@@ -383,141 +478,33 @@ fn render_goal_sprites(
                 (gx, gy)
             };
 
-            // crear enemy temporal con la key 'p' (la estatua) y dibujarlo con draw_sprite
-            let tmp = Enemy::new(sx, sy, vec!['p'], 1);
-            draw_sprite(framebuffer, player, &tmp, texture_cache, depth_buffer);
+            // la estatua es más alta que las paredes (en el último piso el techo es el cielo)
+            sprites.push(Sprite::new(Vector2::new(sx, sy), 'p').sized(150.0, VerticalAnchor::Floor));
         }
     }
+    sprites
 }
 
 
-/// Dibuja un minimapa en la esquina inferior derecha del framebuffer.
-/// - `block_size` es el tamaño (en px) de una celda del mundo (100).
-pub fn draw_minimap(
-    framebuffer: &mut Framebuffer,
-    maze: &Maze,
-    player: &Player,
-    enemies: &[Enemy],
-    block_size: usize,
-) {
-    // Tamaño del minimapa en píxeles (ajusta si quieres más grande/pequeño)
-    const MAP_W: usize = 260;
-    const MAP_H: usize = 160;
-    const PAD: usize = 10;
-
-    let fb_w = framebuffer.width as usize;
-    let fb_h = framebuffer.height as usize;
-
-    // Origen (esquina inferior derecha)
-    let origin_x = fb_w.saturating_sub(PAD + MAP_W);
-    let origin_y = fb_h.saturating_sub(PAD + MAP_H);
-
-    // Dimensiones del mundo en píxeles (según laberinto)
-    let maze_h = maze.len();
-    let maze_w = if maze_h > 0 { maze[0].len() } else { 0 };
-    if maze_w == 0 || maze_h == 0 { return; }
-
-    let world_w = (maze_w * block_size) as f32;
-    let world_h = (maze_h * block_size) as f32;
-
-    // escala del mundo -> minimapa (mantener proporción)
-    let scale_x = MAP_W as f32 / world_w;
-    let scale_y = MAP_H as f32 / world_h;
-    let scale = scale_x.min(scale_y);
-
-    // centrar el mapa dentro del recuadro minimapa
-    let used_w = world_w * scale;
-    let used_h = world_h * scale;
-    let inner_offset_x = origin_x as f32 + ((MAP_W as f32 - used_w) / 2.0);
-    let inner_offset_y = origin_y as f32 + ((MAP_H as f32 - used_h) / 2.0);
-
-    // fondo minimapa
-    let bg = Color::new(10, 10, 10, 220);
-    framebuffer.set_current_color(bg);
-    for mx in 0..MAP_W {
-        for my in 0..MAP_H {
-            framebuffer.set_pixel((origin_x + mx) as i32, (origin_y + my) as i32);
-        }
-    }
-
-    // Dibujar paredes (cada celda del maze que no sea ' ' ni 'g' será pared)
-    let wall_color = Color::new(160, 160, 160, 255);
-    for j in 0..maze_h {
-        for i in 0..maze_w {
-            let ch = maze[j][i];
-            if ch == ' ' || ch == 'g' || ch == 's' { continue; }
-            let cell_x = inner_offset_x + (i as f32) * (block_size as f32) * scale;
-            let cell_y = inner_offset_y + (j as f32) * (block_size as f32) * scale;
-            let sw = (block_size as f32) * scale;
-            let sh = (block_size as f32) * scale;
-            let ix0 = cell_x.floor() as i32;
-            let iy0 = cell_y.floor() as i32;
-            let ix1 = (cell_x + sw).ceil() as i32;
-            let iy1 = (cell_y + sh).ceil() as i32;
-            framebuffer.set_current_color(wall_color);
-            for px in ix0..ix1 {
-                for py in iy0..iy1 {
-                    framebuffer.set_pixel(px, py);
-                }
-            }
-        }
-    }
-
-    // Dibujar player como punto con flecha de dirección (escala apropiada)
-    let px = inner_offset_x + player.pos.x * scale;
-    let py = inner_offset_y + player.pos.y * scale;
-    let pxi = px as i32;
-    let pyi = py as i32;
-    framebuffer.set_current_color(Color::WHITE);
-    for dx in -2..=2 {
-        for dy in -2..=2 {
-            framebuffer.set_pixel(pxi + dx, pyi + dy);
-        }
-    }
-    // flecha/dirección proporcional al tamaño del minimapa
-    let dir_len_world = (block_size as f32).max(24.0); // longitud en coordenadas del mundo
-    let dir_len = dir_len_world * scale;
-    let dir_x = px + player.a.cos() * dir_len;
-    let dir_y = py + player.a.sin() * dir_len;
-    let steps = 6usize;
-    framebuffer.set_current_color(Color::WHITE);
-    for s in 1..=steps {
-        let t = s as f32 / steps as f32;
-        let lx = px + (dir_x - px) * t;
-        let ly = py + (dir_y - py) * t;
-        framebuffer.set_pixel(lx as i32, ly as i32);
-    }
-
-    // Dibujar enemigos
-    let enemy_color = Color::new(220, 40, 40, 255);
-    for e in enemies {
-        let ex = inner_offset_x + e.pos.x * scale;
-        let ey = inner_offset_y + e.pos.y * scale;
-        let exi = ex as i32;
-        let eyi = ey as i32;
-        framebuffer.set_current_color(enemy_color);
-        // punto 3x3
-        for dx in -1..=1 {
-            for dy in -1..=1 {
-                framebuffer.set_pixel(exi + dx, eyi + dy);
-            }
-        }
-    }
-
-    // Borde del minimapa
-    let border_color = Color::new(220, 220, 220, 180);
-    framebuffer.set_current_color(border_color);
-    for x in 0..MAP_W {
-        framebuffer.set_pixel((origin_x + x) as i32, origin_y as i32);
-        framebuffer.set_pixel((origin_x + x) as i32, (origin_y + MAP_H - 1) as i32);
-    }
-    for y in 0..MAP_H {
-        framebuffer.set_pixel(origin_x as i32, (origin_y + y) as i32);
-        framebuffer.set_pixel((origin_x + MAP_W - 1) as i32, (origin_y + y) as i32);
-    }
+/// Traduce el estado del juego a la fuerza de cada efecto de post-proceso.
+/// `hit` va de 1 (recién golpeado) a 0 (fin de la invulnerabilidad).
+fn drive_effects(postfx: &mut PostFx, player: &Player, enemies: &[Enemy], lives: i32, max_lives: i32, hit: f32) {
+    // miedo: 0 con Jack lejos (más de 700 px), 1 pegado al jugador
+    let nearest = enemies
+        .iter()
+        .map(|e| ((e.pos.x - player.pos.x).powi(2) + (e.pos.y - player.pos.y).powi(2)).sqrt())
+        .fold(f32::INFINITY, f32::min);
+    let fear = (1.0 - (nearest - 80.0) / 620.0).clamp(0.0, 1.0);
+    let wounded = 1.0 - lives as f32 / max_lives.max(1) as f32;
+
+    postfx.set_strength(Effect::Grain, 0.35 + 0.65 * fear);
+    postfx.set_strength(Effect::Scanlines, 1.0);
+    postfx.set_strength(Effect::ChromaticAberration, fear * fear + hit * 0.5);
+    postfx.set_strength(Effect::Desaturate, wounded * 0.7 + hit * 0.3);
+    postfx.set_strength(Effect::DamageFlash, hit);
+    postfx.set_strength(Effect::Shake, hit * 0.8 + (fear - 0.8).max(0.0));
 }
 
-
 fn tile_center_pos(i: usize, j: usize, block_size: usize) -> Vector2 {
     Vector2::new(
         i as f32 * block_size as f32 + (block_size as f32) * 0.5,
@@ -733,8 +720,7 @@ fn find_spawn_reachable(
 }
 
 fn title_screen(
-    window: &mut RaylibHandle,
-    thread: &RaylibThread,
+    window: &mut RaylibPresenter,
     fb_w: i32,
     fb_h: i32,
     levels: &[i32],
@@ -801,13 +787,13 @@ fn title_screen(
         let title = "Five Floors at UVG";
         let subtitle = "Usa las flechas para navegar y moverte, y la E para avanzar entre niveles. Esc para salir";
 
-        // Centro x aproximado (tu framebuffer tiene draw_text)
-        let title_x = (framebuffer.width / 2) - 180;
+        // todo centrado en x (el texto se mide al dibujarse)
+        let center_x = framebuffer.width / 2;
         let mut y = 120;
-        framebuffer.draw_text(title, title_x, y, 48, Color::GREEN);
+        framebuffer.draw_text_aligned(title, center_x, y, 48, Color::GREEN, Align::Center);
         y += 64;
-        framebuffer.draw_text(subtitle, title_x - 200, y, 20, Color::WHITE);
-        y += 54;
+        y += framebuffer.draw_text_wrapped(subtitle, center_x, y, framebuffer.width - 200, 20, Color::WHITE, Align::Center);
+        y += 32;
 
         // menú
         for (i, item) in menu_items.iter().enumerate() {
@@ -816,7 +802,7 @@ fn title_screen(
             } else {
                 Color::WHITE
             };
-            framebuffer.draw_text(item, title_x - 100, y, 28, color);
+            framebuffer.draw_text_aligned(item, center_x, y, 28, color, Align::Center);
             y += 42;
         }
 
@@ -825,16 +811,16 @@ fn title_screen(
             y += 8;
             let info = format!("Seleccionar nivel:   <  {}  >",
                                levels[level_choice]);
-            framebuffer.draw_text(&info, title_x - 100, y, 28, Color::GREEN);
+            framebuffer.draw_text_aligned(&info, center_x, y, 28, Color::GREEN, Align::Center);
             y += 40;
-            framebuffer.draw_text("Presiona Enter para empezar con el nivel seleccionado", title_x - 160, y, 16, Color::WHITE);
+            framebuffer.draw_text_aligned("Presiona Enter para empezar con el nivel seleccionado", center_x, y, 16, Color::WHITE, Align::Center);
         } else {
             y += 8;
-            framebuffer.draw_text("Pulsa Enter para seleccionar la opción", title_x - 100, y, 18, Color::WHITE);
+            framebuffer.draw_text_aligned("Pulsa Enter para seleccionar la opción", center_x, y, 18, Color::WHITE, Align::Center);
         }
 
         // swap al final de frame
-        framebuffer.swap_buffers(window, thread);
+        framebuffer.swap_buffers(window);
 
         // pequeña espera para evitar quemar CPU (opcional)
         std::thread::sleep(std::time::Duration::from_millis(10));
@@ -846,8 +832,7 @@ fn title_screen(
 
 
 fn end_screen(
-    window: &mut RaylibHandle,
-    thread: &RaylibThread,
+    window: &mut RaylibPresenter,
     fb_w: i32,
     fb_h: i32,
     title: &str,
@@ -880,53 +865,65 @@ fn end_screen(
         }
 
         // dibujado
-        let title_x = (framebuffer.width / 2) - 220;
+        let center_x = framebuffer.width / 2;
         let mut y = 120;
-        framebuffer.draw_text(title, title_x, y, 52, Color::GREEN);
+        framebuffer.draw_text_aligned(title, center_x, y, 52, Color::GREEN, Align::Center);
         y += 68;
-        framebuffer.draw_text(message, title_x - 60, y, 20, Color::WHITE);
-        y += 60;
+        y += framebuffer.draw_text_wrapped(message, center_x, y, framebuffer.width - 200, 20, Color::WHITE, Align::Center);
+        y += 38;
 
-        // opciones
+        // opciones, lado a lado alrededor del centro
         for (i, opt) in options.iter().enumerate() {
             let color = if i == idx { Color::PERU } else { Color::WHITE };
-            framebuffer.draw_text(opt, title_x + (i as i32) * 180, y, 36, color);
+            let x = center_x + (i as i32 * 2 - 1) * 120;
+            framebuffer.draw_text_aligned(opt, x, y, 36, color, Align::Center);
         }
-        framebuffer.swap_buffers(window, thread);
+        framebuffer.swap_buffers(window);
         std::thread::sleep(std::time::Duration::from_millis(10));
     }
     None
 }
 
 fn victory_screen(
-    window: &mut RaylibHandle,
-    thread: &RaylibThread,
+    window: &mut RaylibPresenter,
     fb_w: i32,
     fb_h: i32,
 ) -> Option<bool> {
-    end_screen(window, thread, fb_w, fb_h, "¡Escapaste!", "Llegaste al último piso y escapaste de Jack. ¿Qué deseas hacer?")
+    end_screen(window, fb_w, fb_h, "¡Escapaste!", "Llegaste al último piso y escapaste de Jack. ¿Qué deseas hacer?")
 }
 
 fn game_over_screen(
-    window: &mut RaylibHandle,
-    thread: &RaylibThread,
+    window: &mut RaylibPresenter,
     fb_w: i32,
     fb_h: i32,
 ) -> Option<bool> {
-    end_screen(window, thread, fb_w, fb_h, "Game Over", "Has perdido todas tus vidas. Jack te capturó de por vida :(")
+    end_screen(window, fb_w, fb_h, "Game Over", "Has perdido todas tus vidas. Jack te capturó de por vida :(")
 }
 
 
 fn main() {
+    // modo sin ventana (render a PNG / comparación con imágenes de referencia)
+    let args: Vec<String> = std::env::args().collect();
+    if let Some(code) = headless::run_cli(&args) {
+        std::process::exit(code);
+    }
+
     let window_width = 1300;
     let window_height = 900;
     let block_size = 100;
+    let settings = Settings::load("settings.txt");
+    let render_threads = if settings.render_threads > 0 {
+        settings.render_threads
+    } else {
+        thread::available_parallelism().map_or(1, |n| n.get()).min(8)
+    };
 
-    let (mut window, raylib_thread) = raylib::init()
+    let (rl, raylib_thread) = raylib::init()
         .size(window_width, window_height)
         .title("Five Floors at UVG")
         .log_level(TraceLogLevel::LOG_WARNING)
         .build();
+    let mut window = RaylibPresenter::new(rl, raylib_thread);
     unsafe {
         ffi::InitAudioDevice();
     }
@@ -935,31 +932,25 @@ fn main() {
     let mut framebuffer = Framebuffer::new(window_width as i32, window_height as i32,Color::BLACK);
     framebuffer.set_background_color(Color::new(50, 50, 100, 255));
 
-    let levels = vec![3,4,5,6,7]; // definir niveles aquí temprano
-    if let Some(start_level) = title_screen(&mut window, &raylib_thread, window_width as i32, window_height as i32, &levels) {
-        let mut level_index = levels.iter().position(|&lv| lv == start_level).unwrap_or(0);
-        let mut current_level = levels[level_index];
-
-        // Load the maze once before the loop
-        let mut maze = load_maze(maze_filename_for_level(current_level));
-        let player_pos = if let Some((si, sj)) = find_tile(&maze, 's') {
-            tile_center_pos(si, sj, block_size)
-        } else if let Some((gi, gj)) = find_tile(&maze, 'g') {
-            tile_center_pos(gi, gj, block_size)
-        } else {
-            let temp_player = Vector2::new((block_size/2) as f32, (block_size/2) as f32);
-            find_spawn_reachable(&maze, block_size, temp_player, 0)
-        };
-        let mut player = Player{pos:player_pos, a: PI/3.0, fov: PI/2.0 };
-        let texture_cache = TextureManager::new(&mut window, &raylib_thread);
-        let mut depth_buffer = vec![f32::INFINITY; window_width as usize];
-        let mut enemies = vec![Enemy::new(375.0, 400.0, vec!['e', 'E'], 20)];
-        let mut lives: i32 = 3;
-        let max_lives: i32 = 3;
-        let mut invuln_timer: f32 = 0.0;
-        const INVULN_DURATION: f32 = 1.5_f32; 
-        let base_enemy_speed = 2.9_f32;
-        let mut levels_passed: usize = 0; 
+    let levels = LEVELS.to_vec(); // definir niveles aquí temprano
+    if let Some(start_level) = title_screen(&mut window, window_width as i32, window_height as i32, &levels) {
+        let mut game = Game::new(&levels, start_level);
+        let mut texture_cache = TextureManager::new();
+        texture_cache.set_filtering(settings.mipmaps, settings.bilinear);
+        let sprite_limits = SpriteLimits { near: settings.sprite_near, far: settings.sprite_far };
+        if settings.palette || settings.retro {
+            texture_cache.enable_palette(settings.retro);
+        }
+        // Vista 3D a resolución interna (la GPU la estira al presentarla, debajo del HUD)
+        let scene_bg = Color::new(50, 50, 100, 255);
+        let mut render_scale = RenderScale::new(
+            settings.render_scale,
+            settings.dynamic_resolution,
+            Duration::from_secs_f32(0.9 / 60.0), // presupuesto: un poco menos que un frame a 60 fps
+        );
+        let (scene_w, scene_h) = render_scale.scaled_size(window_width, window_height);
+        let mut scene = Framebuffer::new(scene_w, scene_h, scene_bg);
+        let mut depth_buffer = vec![f32::INFINITY; scene_w as usize];
 
         let hit_path = CString::new("assets/hit_sound.wav").expect("CString::new failed");
         let hit_sound = unsafe { ffi::LoadSound(hit_path.as_ptr()) };
@@ -967,242 +958,189 @@ fn main() {
         let music = unsafe { ffi::LoadMusicStream(music_path.as_ptr()) };
         unsafe { ffi::PlayMusicStream(music); }
 
-        let mut level_transition_cooldown: f32 = 0.0;
-        const LEVEL_TRANSITION_COOLDOWN: f32 = 0.6_f32;
+        let mut flashlight = Flashlight::new();
+        let mut particles = Particles::new();
+
+        let mut postfx = PostFx::new();
+        postfx.set_enabled(Effect::Grain, settings.grain);
+        postfx.set_enabled(Effect::Scanlines, settings.scanlines);
+        postfx.set_enabled(Effect::ChromaticAberration, settings.chromatic_aberration);
+        postfx.set_enabled(Effect::Desaturate, settings.desaturate);
+        postfx.set_enabled(Effect::DamageFlash, settings.damage_flash);
+        postfx.set_enabled(Effect::Shake, settings.screen_shake);
+
+        let mut capture = Capture::new(settings.capture_every, settings.capture_gif);
+        // aviso pasajero del HUD: (texto, segundos restantes)
+        let problems = texture_cache.problems();
+        let mut notice: Option<(String, f32)> = if problems.is_empty() {
+            None
+        } else {
+            Some((format!("Faltan texturas o hay errores en el manifiesto ({}); ver la consola", problems.len()), 6.0))
+        };
+        let mut last_reload_check: f32 = 0.0;
+
+        let mut hud = Hud::new();
+        hud.set_enabled(Widget::Fps, settings.hud_fps);
+        hud.set_enabled(Widget::Floor, settings.hud_floor);
+        hud.set_enabled(Widget::Health, settings.hud_health);
+        hud.set_enabled(Widget::Minimap, settings.hud_minimap);
 
         while !window.window_should_close() {
+            let frame_start = Instant::now();
             unsafe { ffi::UpdateMusicStream(music); }
             let dt = 1.0_f32 / 60.0_f32;
-            if level_transition_cooldown > 0.0 {
-                level_transition_cooldown -= dt;
-                if level_transition_cooldown < 0.0 { level_transition_cooldown = 0.0; }
-            }
 
             framebuffer.clear();
-            process_events(&window, &mut player, &maze);
-            // 1. clear framebuffer
+            process_events(&window, &mut game.player, &game.maze);
             let mut mode = "3D";
-            let enemy_speed = base_enemy_speed + 0.2_f32 * (levels_passed as f32);
-            for e in enemies.iter_mut() {
-                e.update(&player, &maze, block_size, enemy_speed);
-            }
-                    if invuln_timer > 0.0 {
-                        invuln_timer -= dt;
-                        if invuln_timer < 0.0 { invuln_timer = 0.0; }
-                    }
-
-            let collision_radius = 28.0_f32; // radio en pixeles para considerar "contacto"
-            for enemy in enemies.iter_mut() {
-                let dx = enemy.pos.x - player.pos.x;
-                let dy = enemy.pos.y - player.pos.y;
-                let dist = (dx*dx + dy*dy).sqrt();
 
-                if dist <= collision_radius && invuln_timer <= 0.0 && lives > 0 {
-                    // perder 1 vida
-                    lives -= 1;
-                    invuln_timer = INVULN_DURATION;
-
-                    // reproducir sonido si existe
-                    unsafe {
-                        ffi::PlaySound(hit_sound);
-                    }
-                    break;
+            // reglas del juego (enemigos, golpes); aquí solo lo que se ve y se oye
+            let event = game.update(dt, 1.0);
+            if matches!(event, Some(Event::Hit | Event::Caught)) {
+                if settings.particles {
+                    // salpicadura delante de la cámara, alejándose
+                    let player = &game.player;
+                    let front = Vector2::new(player.pos.x + player.a.cos() * 30.0, player.pos.y + player.a.sin() * 30.0);
+                    particles.blood_burst(front, player.a);
+                }
+                unsafe {
+                    ffi::PlaySound(hit_sound);
                 }
             }
-
-            if lives <= 0 {
-                // asegurar no tener vidas negativas
-                lives = 0;
-                if let Some(choice_restart) = game_over_screen(&mut window, &raylib_thread, window_width as i32, window_height as i32) {
-                    if choice_restart {
-                        // Reiniciar juego (igual que reinicio tras victory)
-                        level_index = 0;
-                        current_level = levels[level_index];
-                        maze = load_maze(maze_filename_for_level(current_level));
-                        // spawn player en la 'g' si existe, o centro libre
-                        if let Some((gi, gj)) = find_tile(&maze, 'g') {
-                            player.pos = tile_center_pos(gi, gj, block_size);
-                        } else {
-                            player.pos = find_nearest_free_to_center(&maze, block_size);
-                        }
-                        // reset enemigo al centro del nuevo mapa
-                        let ec = find_nearest_free_to_center(&maze, block_size);
-                        enemies = vec![Enemy::new(250.0, 250.0, vec!['e', 'E'], 20)];
-                        lives = max_lives;
-                        invuln_timer = 0.0;
-                        levels_passed = 0;
-                        level_transition_cooldown = LEVEL_TRANSITION_COOLDOWN;
-                        // continue game loop (se reinició)
-                    } else {
-                        // Salir del juego
-                        break;
+            if event == Some(Event::Caught) {
+                match game_over_screen(&mut window, window_width as i32, window_height as i32) {
+                    Some(true) => {
+                        game.restart();
+                        particles.clear();
                     }
-                } else {
-                    // ventana cerrada por el usuario
-                    break;
+                    // salir del juego o ventana cerrada
+                    _ => break,
                 }
             }
 
-            // Encolar estado de vidas para que el framebuffer lo dibuje en swap_buffers
-            framebuffer.queue_health(lives, max_lives);
-
             if window.is_key_down(KeyboardKey::KEY_M) {
                 mode = if mode =="2D" {"3D"} else {"2D"};
             }
 
-            let cell_i = ((player.pos.x / block_size as f32).floor() as isize).max(0) as usize;
-            let cell_j = ((player.pos.y / block_size as f32).floor() as isize).max(0) as usize;
-            let at_exit = maze.get(cell_j).and_then(|row| row.get(cell_i)).map_or(false, |&c| c == 'g');
-
-            if at_exit && window.is_key_pressed(KeyboardKey::KEY_E) {
-                // guardamos coordenada de salida en el mapa antiguo (la 'g' donde el jugador estaba)
-                let prev_exit = Some((cell_i, cell_j));
-
-                // avanzar de nivel (si hay)
-                if level_index + 1 < levels.len() {
-                    level_index += 1;
-                    current_level = levels[level_index];
-                    // re-cargar maze nuevo (destino)
-                    let filename = maze_filename_for_level(current_level);
-                    let new_maze = load_maze(filename);
-
-                    // --- COLOCAR JUGADOR en la misma CELDA (pi,pj) del mapa anterior ---
-                    // si el nuevo mapa contiene 's' úsalo; si no, usar prev_exit como antes.
-                    if let Some((si, sj)) = find_tile(&new_maze, 's') {
-                        // spawn explícito en la 's' del nuevo mapa (útil para mapa final 7)
-                        player.pos = tile_center_pos(si, sj, block_size);
-                    } else if let Some((pi, pj)) = prev_exit {
-                        let maze_h_new = new_maze.len();
-                        let maze_w_new = if maze_h_new > 0 { new_maze[0].len() } else { 0 };
-
-                        if maze_w_new == 0 || maze_h_new == 0 {
-                            player.pos = find_nearest_free_to_center(&new_maze, block_size);
-                        } else if pi < maze_w_new && pj < maze_h_new {
-                            let ch = new_maze[pj][pi];
-                            if ch == ' ' || ch == 'g' {
-                                player.pos = tile_center_pos(pi, pj, block_size);
-                            } else {
-                                player.pos = find_nearest_free_around(&new_maze, block_size, pi, pj, 8);
-                            }
-                        } else {
-                            let clamped_i = if maze_w_new == 0 { 0 } else { pi.min(maze_w_new.saturating_sub(1)) };
-                            let clamped_j = if maze_h_new == 0 { 0 } else { pj.min(maze_h_new.saturating_sub(1)) };
-                            player.pos = find_nearest_free_around(&new_maze, block_size, clamped_i, clamped_j, 8);
-                        }
-                    } else {
-                        // fallback si no hay prev_exit ni 's'
-                        player.pos = find_nearest_free_to_center(&new_maze, block_size);
-                    }
-
-                    player.a = PI / 3.0; // reiniciar la rotación
-                    let enemy_spawn = find_spawn_reachable(&new_maze, block_size, player.pos, 3);
-                    enemies = vec![Enemy::new(enemy_spawn.x, enemy_spawn.y, vec!['e','E'], 20)];
-                    // refill vidas
-                    lives = max_lives;
-                    invuln_timer = 0.0;
-                    // aumentar contador de niveles completados
-                    levels_passed += 1;
-                    // re-asignar el maze cargado (nuevo)
-                    maze = new_maze;
-                    // cooldown para evitar triggers repetidos
-                    level_transition_cooldown = LEVEL_TRANSITION_COOLDOWN;
-                } 
-                else {
-                    // último nivel: pantalla de victoria
-                    if let Some(choice_restart) = victory_screen(&mut window, &raylib_thread, window_width as i32, window_height as i32) {
-                        if choice_restart {
-                            // reiniciar como antes (volver al primer nivel)
-                            level_index = 0;
-                            current_level = levels[level_index];
-                            maze = load_maze(maze_filename_for_level(current_level));
-                            // spawn player en la 'g' si existe, o centro libre
-                            if let Some((gi, gj)) = find_tile(&maze, 'g') {
-                                player.pos = tile_center_pos(gi, gj, block_size);
-                            } else {
-                                player.pos = find_nearest_free_to_center(&maze, block_size);
-                            }
-                            // reset enemigo al centro del nuevo mapa
-                            let ec = find_nearest_free_to_center(&maze, block_size);
-                            enemies = vec![Enemy::new(250.0, 250.0, vec!['e', 'E'], 20)];
-                            lives = max_lives;
-                            levels_passed = 0;
-                            level_transition_cooldown = LEVEL_TRANSITION_COOLDOWN;
-                        } 
-                        else { //decidió salir
-                            break;
+            if window.is_key_pressed(KeyboardKey::KEY_E) {
+                match game.use_exit() {
+                    Some(Event::LevelChanged) => particles.clear(),
+                    // último piso: pantalla de victoria
+                    Some(Event::Escaped) => match victory_screen(&mut window, window_width as i32, window_height as i32) {
+                        Some(true) => {
+                            game.restart();
+                            particles.clear();
                         }
-                    } 
-                    else {
-                        break;
-                    }
+                        _ => break,
+                    },
+                    _ => {}
                 }
-
             }
 
+            // Linterna: se cierra (cono más estrecho y corto) al acercarse Jack
+            flashlight.follow_enemies(&game.player, &game.enemies);
+
+            // reloj global: anima luces y texturas del nivel
+            let time = window.get_time() as f32;
+
+            // recarga en caliente: revisar los archivos de texturas un par de veces por segundo
+            if time - last_reload_check > 0.5 {
+                last_reload_check = time;
+                let reloaded = texture_cache.reload_changed();
+                if !reloaded.is_empty() {
+                    let keys: String = reloaded.iter().collect();
+                    notice = Some((format!("Texturas recargadas: {}", keys), 2.5));
+                }
+            }
+            game.lights.update(time, dt, &game.player, &game.enemies, &game.maze, block_size);
+            if settings.particles {
+                particles.update(dt, &game.player, &game.lights, &game.maze, block_size);
+            }
 
+            // vista 3D a resolución interna: va aparte y se junta con el HUD al presentar
+            let layered = mode == "3D" && (scene.width != framebuffer.width || scene.height != framebuffer.height);
             if mode == "2D"{
-                render_maze(&mut framebuffer, &maze, block_size,&player,current_level as usize);
+                render_maze(&mut framebuffer, &game.maze, block_size,&game.player,game.current_level() as usize);
             }
             else {
+                // a escala 1.0 se dibuja directo; si no, en `scene`, que se estira recién al presentarla
+                // (el framebuffer queda como capa transparente para el HUD)
+                let view = if layered {
+                    framebuffer.clear_transparent();
+                    scene.clear();
+                    &mut scene
+                } else {
+                    &mut framebuffer
+                };
+
                 for d in depth_buffer.iter_mut() { *d = f32::INFINITY; }
-                render_world(&mut framebuffer,&player,&maze,&texture_cache,&mut depth_buffer,current_level as usize);
-                render_enemies(&mut framebuffer, &player, &texture_cache, &depth_buffer, &enemies);
-                render_goal_sprites(&mut framebuffer, &player, &maze, &texture_cache, &depth_buffer, current_level as usize);
-                draw_minimap(&mut framebuffer, &maze, &player, &enemies, block_size);
+                render_world(view,&game.player,&game.maze,&texture_cache,&mut depth_buffer,game.current_level() as usize, &flashlight, &game.lights, &game.decals, time, render_threads);
+                let mut sprites = world_sprites(&game.player, &game.maze, &game.enemies, &game.lights, game.current_level() as usize);
+                sprites.extend(particles.sprites());
+                render_sprites(view, &game.player, &sprites, &texture_cache, &depth_buffer, &flashlight, &game.lights, sprite_limits);
+
+                // post-proceso sobre la vista 3D (el minimapa y el HUD quedan limpios)
+                drive_effects(&mut postfx, &game.player, &game.enemies, game.lives, game.max_lives, game.invulnerability());
+                postfx.set_pixel_scale(view.width as f32 / window_width as f32);
+                view.apply_effects(&postfx, time);
             }
 
             {
-                let fps = window.get_fps();
-                let text = format!("FPS: {}", fps);
-                framebuffer.draw_text(&text, 10, 10, 20, Color::WHITE); // <- nuevo método
-
-                let piso_text = format!("Piso {}", current_level);
-                let font_size = 28;
-                // ancho aproximado (estimación) para centrar: asumir 0.6 * font_size por carácter
-                let approx_text_width = piso_text.len() as f32 * (font_size as f32) * 0.6;
-                let x_center = (framebuffer.width as f32 / 2.0 - approx_text_width / 2.0) as i32;
-                framebuffer.draw_text(&piso_text, x_center, 6, font_size, Color::YELLOW);
-
-                // Calcula la distancia mínima del jugador a cualquier enemigo
-                let mut min_enemy_dist = f32::INFINITY;
-                for enemy in enemies.iter() {
-                    let dx = enemy.pos.x - player.pos.x;
-                    let dy = enemy.pos.y - player.pos.y;
-                    let d = (dx*dx + dy*dy).sqrt();
-                    if d < min_enemy_dist { min_enemy_dist = d; }
+                let mut hud_state = HudState {
+                    fps: window.get_fps() as i32,
+                    level: game.current_level(),
+                    lives: game.lives,
+                    max_lives: game.max_lives,
+                    maze: &game.maze,
+                    player: &game.player,
+                    enemies: &game.enemies,
+                    block_size,
+                    view_3d: mode == "3D",
+                    recording: capture.is_recording(),
+                    message: None,
+                };
+                hud.draw(&mut framebuffer, &hud_state);
+
+                // capturas: el frame ya está completo; los avisos ("REC", mensajes) se dibujan después.
+                // Con la escena aparte, se juntan en CPU solo si hay algo que guardar
+                let screenshot = window.is_key_pressed(KeyboardKey::KEY_F12);
+                let composed = (layered && (screenshot || capture.is_recording()))
+                    .then(|| Framebuffer::composed(&scene, &framebuffer));
+                let frame = composed.as_ref().unwrap_or(&framebuffer);
+                capture.record(frame);
+                if screenshot {
+                    let message = match capture.screenshot(frame) {
+                        Ok(path) => format!("Captura guardada: {}", path),
+                        Err(e) => e,
+                    };
+                    notice = Some((message, 2.5));
+                }
+                if window.is_key_pressed(KeyboardKey::KEY_F10) {
+                    notice = Some((capture.toggle_recording(framebuffer.width, framebuffer.height), 2.5));
+                }
+                hud_state.recording = capture.is_recording();
+                hud_state.message = notice.as_ref().map(|(text, _)| text.as_str());
+                hud.draw_notices(&mut framebuffer, &hud_state);
+                if let Some((_, seconds_left)) = &mut notice {
+                    *seconds_left -= dt;
+                    if *seconds_left <= 0.0 {
+                        notice = None;
+                    }
                 }
 
-                let max_effect_distance = 1500.0_f32; // comienza a afectar desde más lejos
-                let min_effect_distance = 80.0_f32;  // muy cerca = círculo mínimo
-                let max_radius = (framebuffer.width.min(framebuffer.height)) as f32 * 0.9; // radio máximo
-                let min_radius = 40.0_f32; // radio mínimo visible al acercarse mucho
-
-                // oscuridad base
-                let min_darkness = 0.5_f32; // lejos = oscuridad leve
-                let max_darkness = 1.0_f32; // cerca = oscuridad total
-
-                // calcular factor de proximidad t en [0..1]
-                let t = if min_enemy_dist >= max_effect_distance {
-                    0.0_f32
-                } else if min_enemy_dist <= min_effect_distance {
-                    1.0_f32
+                // medir el trabajo del frame antes de swap_buffers (que espera al siguiente frame)
+                let work_time = frame_start.elapsed();
+                if layered {
+                    framebuffer.swap_buffers_over(&scene, &mut window);
                 } else {
-                    (max_effect_distance - min_enemy_dist) / (max_effect_distance - min_effect_distance)
-                };
-
-                // radio interpolado
-                let radius = max_radius * (1.0 - t) + min_radius * t;
-
-                // oscuridad interpolada
-                let darkness = min_darkness + (max_darkness - min_darkness) * t;
-
-                // centro de pantalla (linterna centrada)
-                let center_x = (framebuffer.width / 2) as i32;
-                let center_y = (framebuffer.height / 2) as i32;
+                    framebuffer.swap_buffers(&mut window);
+                }
 
-                // aplicar efecto
-                framebuffer.draw_vignette(center_x, center_y, radius, darkness);
-                framebuffer.swap_buffers(&mut window, &raylib_thread);
+                if render_scale.update(work_time) {
+                    let (scene_w, scene_h) = render_scale.scaled_size(window_width, window_height);
+                    scene = Framebuffer::new(scene_w, scene_h, scene_bg);
+                    depth_buffer = vec![f32::INFINITY; scene_w as usize];
+                }
             }
         }
 
@@ -1217,3 +1155,61 @@ fn main() {
         return;
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Vista 3D del piso impar desde una pose fija, dibujada con `threads` hilos.
+    fn render_odd_floor(framebuffer: &mut Framebuffer, texture_cache: &TextureManager, threads: usize) {
+        let block_size = 100;
+        let maze = load_maze(&maze_filename_for_level(3));
+        let lights = LightSet::load(&lights_filename_for_level(3), &maze, block_size);
+        let decals = Decals::load(&decals_filename_for_level(3));
+        let player = Player { pos: Vector2::new(1350.0, 180.0), a: (-35.0f32).to_radians(), fov: PI / 2.0 };
+        let mut depth_buffer = vec![f32::INFINITY; framebuffer.width as usize];
+        render_world(framebuffer, &player, &maze, texture_cache, &mut depth_buffer, 3, &Flashlight::new(), &lights, &decals, 0.0, threads);
+    }
+
+    #[test]
+    fn threads_do_not_change_the_image() {
+        let texture_cache = TextureManager::new();
+        // ancho que no se reparte parejo entre los hilos
+        let (width, height) = (203, 150);
+        let mut single = Framebuffer::new(width, height, Color::BLACK);
+        render_odd_floor(&mut single, &texture_cache, 1);
+
+        for threads in [2, 3, 8, 203, 500] {
+            let mut framebuffer = Framebuffer::new(width, height, Color::BLACK);
+            render_odd_floor(&mut framebuffer, &texture_cache, threads);
+            assert!(framebuffer.pixels() == single.pixels(), "{} hilos dan otra imagen", threads);
+        }
+
+        // el buffer por columnas se reusa: un segundo frame sobre el mismo framebuffer da lo mismo
+        let first = single.pixels().to_vec();
+        render_odd_floor(&mut single, &texture_cache, 4);
+        assert!(single.pixels() == first.as_slice());
+    }
+
+    #[test]
+    fn lamp_glow_follows_the_lamp_level() {
+        let block_size = 100;
+        let maze = load_maze(&maze_filename_for_level(3));
+        let mut lights = LightSet::load(&lights_filename_for_level(3), &maze, block_size);
+        let player = Player { pos: Vector2::new(350.0, 150.0), a: 0.0, fov: PI / 2.0 };
+        assert!(!lights.lights.is_empty());
+
+        // varios momentos, para pasar por parpadeos y luces apagadas
+        for step in 0..40 {
+            lights.update(step as f32 * 0.37, 0.37, &player, &[], &maze, block_size);
+            let glows: Vec<Sprite> = world_sprites(&player, &maze, &[], &lights, 3).into_iter().filter(|s| s.key == LAMP_KEY).collect();
+            let lit: Vec<_> = lights.lights.iter().filter(|l| l.level() >= 0.01).collect();
+            assert_eq!(glows.len(), lit.len());
+            for (glow, light) in glows.iter().zip(lit) {
+                assert!(glow.emissive);
+                assert_eq!(glow.alpha, light.level());
+                assert_eq!((glow.pos.x, glow.pos.y), (light.pos.x, light.pos.y));
+            }
+        }
+    }
+}
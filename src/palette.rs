@@ -0,0 +1,226 @@
+use raylib::prelude::*;
+
+/// Niveles de luz de las tablas (0 = negro, LIGHT_LEVELS - 1 = color de la textura).
+pub const LIGHT_LEVELS: usize = 32;
+const MAX_COLORS: usize = 256;
+// la tabla inversa (color -> índice) usa 5 bits por canal
+const INVERSE_BITS: u32 = 5;
+const INVERSE_SIDE: usize = 1 << INVERSE_BITS;
+
+/// Paleta compartida de hasta 256 colores con sus tablas de luz precalculadas (como los
+/// colormaps de Doom): sombrear un texel indexado es una sola lectura de tabla.
+pub struct Palette {
+    colors: Vec<Color>,
+    // shades[nivel * 256 + índice] = color del índice con esa luz
+    shades: Vec<Color>,
+    // índice más cercano para cada color (5 bits por canal)
+    inverse: Vec<u8>,
+    retro: bool,
+}
+
+impl Palette {
+    /// Arma la paleta con los colores más representativos de `samples` (corte por la mediana).
+    /// Con `retro` las tablas de luz también devuelven colores de la paleta, como en los
+    /// juegos de 8 bits; si no, el color sombreado exacto.
+    pub fn from_samples(samples: &[Color], retro: bool) -> Self {
+        let colors = median_cut(samples, MAX_COLORS);
+        let inverse = build_inverse(&colors);
+        let mut palette = Palette { colors, shades: Vec::new(), inverse, retro };
+        palette.shades = palette.build_shades();
+        palette
+    }
+
+    pub fn len(&self) -> usize {
+        self.colors.len()
+    }
+
+    /// Los colores de la paleta (el índice de cada uno es su posición).
+    pub fn colors(&self) -> &[Color] {
+        &self.colors
+    }
+
+    pub fn is_retro(&self) -> bool {
+        self.retro
+    }
+
+    /// Índice del color de la paleta más parecido a `color`.
+    pub fn nearest(&self, color: Color) -> u8 {
+        let shift = 8 - INVERSE_BITS;
+        let (r, g, b) = ((color.r >> shift) as usize, (color.g >> shift) as usize, (color.b >> shift) as usize);
+        self.inverse[(r * INVERSE_SIDE + g) * INVERSE_SIDE + b]
+    }
+
+    /// `color` cambiado por su aproximación en la paleta (conserva el alpha).
+    pub fn quantize(&self, color: Color) -> Color {
+        Color { a: color.a, ..self.colors[self.nearest(color) as usize] }
+    }
+
+    /// Color del índice `index` con luz `level` (ver `light_level`).
+    pub fn shade(&self, index: u8, level: usize) -> Color {
+        self.shades[level.min(LIGHT_LEVELS - 1) * MAX_COLORS + index as usize]
+    }
+
+    fn build_shades(&self) -> Vec<Color> {
+        let mut shades = vec![Color::BLACK; LIGHT_LEVELS * MAX_COLORS];
+        for level in 0..LIGHT_LEVELS {
+            let k = level as f32 / (LIGHT_LEVELS - 1) as f32;
+            for (index, c) in self.colors.iter().enumerate() {
+                let lit = Color::new(
+                    (c.r as f32 * k) as u8,
+                    (c.g as f32 * k) as u8,
+                    (c.b as f32 * k) as u8,
+                    255,
+                );
+                shades[level * MAX_COLORS + index] = if self.retro { self.quantize(lit) } else { lit };
+            }
+        }
+        shades
+    }
+}
+
+/// Nivel de luz de las tablas para la linterna (`flash`) más las lámparas (`point`, por canal).
+/// Las tablas solo guardan intensidad: el tinte de las lámparas cuenta por su brillo.
+pub fn light_level(flash: f32, point: [f32; 3]) -> usize {
+    let lamp = 0.299 * point[0] + 0.587 * point[1] + 0.114 * point[2];
+    ((flash + lamp).clamp(0.0, 1.0) * (LIGHT_LEVELS - 1) as f32).round() as usize
+}
+
+/// Corte por la mediana: parte el conjunto de colores por el canal de mayor rango hasta tener
+/// `max_colors` grupos y devuelve el promedio de cada uno.
+fn median_cut(samples: &[Color], max_colors: usize) -> Vec<Color> {
+    let mut boxes: Vec<Vec<[u8; 3]>> = vec![samples.iter().filter(|c| c.a > 0).map(|c| [c.r, c.g, c.b]).collect()];
+    if boxes[0].is_empty() {
+        return vec![Color::BLACK];
+    }
+
+    while boxes.len() < max_colors {
+        // el grupo con más rango en algún canal (que se pueda partir)
+        let widest = boxes
+            .iter()
+            .enumerate()
+            .filter(|(_, b)| b.len() > 1)
+            .map(|(i, b)| {
+                let (channel, range) = (0..3)
+                    .map(|ch| {
+                        let (lo, hi) = b.iter().fold((255u8, 0u8), |(lo, hi), c| (lo.min(c[ch]), hi.max(c[ch])));
+                        (ch, hi - lo)
+                    })
+                    .max_by_key(|&(_, range)| range)
+                    .unwrap();
+                (i, channel, range)
+            })
+            .filter(|&(_, _, range)| range > 0)
+            .max_by_key(|&(_, _, range)| range);
+        let Some((index, channel, _)) = widest else {
+            break;
+        };
+
+        let mut group = boxes.swap_remove(index);
+        group.sort_unstable_by_key(|c| c[channel]);
+        let upper = group.split_off(group.len() / 2);
+        boxes.push(group);
+        boxes.push(upper);
+    }
+
+    boxes
+        .iter()
+        .map(|b| {
+            let n = b.len() as u32;
+            let sum = b.iter().fold([0u32; 3], |s, c| [s[0] + c[0] as u32, s[1] + c[1] as u32, s[2] + c[2] as u32]);
+            Color::new((sum[0] / n) as u8, (sum[1] / n) as u8, (sum[2] / n) as u8, 255)
+        })
+        .collect()
+}
+
+/// Para cada color de 5 bits por canal, el índice más cercano de la paleta.
+fn build_inverse(colors: &[Color]) -> Vec<u8> {
+    let step = 1 << (8 - INVERSE_BITS);
+    let mut inverse = vec![0u8; INVERSE_SIDE * INVERSE_SIDE * INVERSE_SIDE];
+    for (cell, slot) in inverse.iter_mut().enumerate() {
+        // centro de la celda
+        let r = ((cell / (INVERSE_SIDE * INVERSE_SIDE)) * step + step / 2) as i32;
+        let g = ((cell / INVERSE_SIDE % INVERSE_SIDE) * step + step / 2) as i32;
+        let b = ((cell % INVERSE_SIDE) * step + step / 2) as i32;
+        let mut best = (i32::MAX, 0);
+        for (i, c) in colors.iter().enumerate() {
+            let (dr, dg, db) = (c.r as i32 - r, c.g as i32 - g, c.b as i32 - b);
+            // distancia con el peso aproximado de cada canal para el ojo
+            let d = 3 * dr * dr + 4 * dg * dg + 2 * db * db;
+            if d < best.0 {
+                best = (d, i);
+            }
+        }
+        *slot = best.1 as u8;
+    }
+    inverse
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn samples() -> Vec<Color> {
+        let base = [Color::new(200, 30, 30, 255), Color::new(30, 200, 30, 255), Color::new(30, 30, 200, 255), Color::new(240, 240, 240, 255)];
+        base.iter().cycle().take(400).copied().collect()
+    }
+
+    #[test]
+    fn few_colors_are_kept_exactly() {
+        let palette = Palette::from_samples(&samples(), false);
+        assert_eq!(palette.len(), 4);
+        for c in samples().iter().take(4) {
+            assert_eq!(palette.colors()[palette.nearest(*c) as usize], *c);
+        }
+    }
+
+    #[test]
+    fn nearest_picks_the_closest_color() {
+        let palette = Palette::from_samples(&samples(), false);
+        let reddish = palette.quantize(Color::new(180, 60, 40, 90));
+        assert_eq!(reddish, Color::new(200, 30, 30, 90), "conserva el alpha");
+        assert_eq!(palette.quantize(Color::new(255, 255, 255, 255)), Color::new(240, 240, 240, 255));
+    }
+
+    #[test]
+    fn caps_at_256_colors() {
+        let many: Vec<Color> = (0..4096u32).map(|i| Color::new((i * 7) as u8, (i * 13) as u8, (i / 16) as u8, 255)).collect();
+        let palette = Palette::from_samples(&many, false);
+        assert_eq!(palette.len(), 256);
+        assert_eq!(Palette::from_samples(&[], false).len(), 1);
+    }
+
+    #[test]
+    fn light_ramp_goes_from_black_to_full_color() {
+        let palette = Palette::from_samples(&samples(), false);
+        let i = palette.nearest(Color::new(200, 30, 30, 255));
+        assert_eq!(palette.shade(i, 0), Color::BLACK);
+        assert_eq!(palette.shade(i, LIGHT_LEVELS - 1), Color::new(200, 30, 30, 255));
+        // más allá del último nivel se queda en el color pleno
+        assert_eq!(palette.shade(i, LIGHT_LEVELS + 10), palette.shade(i, LIGHT_LEVELS - 1));
+        let mut last = 0;
+        for level in 0..LIGHT_LEVELS {
+            let r = palette.shade(i, level).r;
+            assert!(r >= last);
+            last = r;
+        }
+    }
+
+    #[test]
+    fn retro_ramp_stays_in_the_palette() {
+        let palette = Palette::from_samples(&samples(), true);
+        for i in 0..palette.len() as u8 {
+            for level in 0..LIGHT_LEVELS {
+                assert!(palette.colors().contains(&palette.shade(i, level)));
+            }
+        }
+    }
+
+    #[test]
+    fn light_level_combines_flashlight_and_lamps() {
+        assert_eq!(light_level(0.0, [0.0; 3]), 0);
+        assert_eq!(light_level(1.0, [0.0; 3]), LIGHT_LEVELS - 1);
+        assert_eq!(light_level(0.5, [0.5; 3]), LIGHT_LEVELS - 1);
+        assert_eq!(light_level(0.5, [0.0; 3]), 16);
+        assert_eq!(light_level(3.0, [1.0; 3]), LIGHT_LEVELS - 1);
+    }
+}
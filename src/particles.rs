@@ -0,0 +1,350 @@
+use raylib::prelude::*;
+use std::f32::consts::TAU;
+
+use crate::light::LightSet;
+use crate::maze::Maze;
+use crate::player::Player;
+use crate::postfx::hash;
+use crate::sprite::{Sprite, VerticalAnchor, WALL_HEIGHT};
+
+// Texturas generadas de cada clase de partícula (ver assets/textures.txt)
+const DUST_KEY: char = ',';
+const SPARK_KEY: char = '^';
+const BLOOD_KEY: char = '~';
+
+// Tope de partículas vivas (las nuevas no entran si ya hay tantas)
+const MAX_PARTICLES: usize = 600;
+// Motas de polvo que flotan alrededor del jugador
+const DUST_COUNT: usize = 40;
+
+/// Una partícula: un billboard pequeño con posición, velocidad y vida propias.
+/// Las unidades son las del mundo (un bloque mide 100) y los tiempos en segundos.
+struct Particle {
+    pos: Vector2, // posición en el plano del mapa
+    z: f32,       // altura sobre el suelo
+    vel: Vector2,
+    vz: f32,
+    gravity: f32, // aceleración hacia el suelo
+    drag: f32,    // fracción de la velocidad que se pierde por segundo
+    life: f32,    // segundos que le quedan
+    max_life: f32,
+    fade_in: f32, // segundos en aparecer (0 = de golpe)
+    size: f32,
+    key: char,
+    emissive: bool,
+    sticks: bool, // al tocar el suelo se queda quieta (sangre); si no, rebota
+}
+
+/// Partículas del piso: polvo en el aire, chispas de lámparas rotas y sangre.
+pub struct Particles {
+    list: Vec<Particle>,
+    counter: u32, // avanza con cada número aleatorio
+}
+
+impl Particles {
+    pub fn new() -> Self {
+        Particles { list: Vec::new(), counter: 0 }
+    }
+
+    /// Quita todas (al cambiar de piso).
+    pub fn clear(&mut self) {
+        self.list.clear();
+    }
+
+    pub fn len(&self) -> usize {
+        self.list.len()
+    }
+
+    /// Número pseudoaleatorio en 0..1.
+    fn random(&mut self) -> f32 {
+        self.counter = self.counter.wrapping_add(1);
+        hash(self.counter, 0x9e37, 0x79b9) as f32 / u32::MAX as f32
+    }
+
+    fn range(&mut self, lo: f32, hi: f32) -> f32 {
+        lo + (hi - lo) * self.random()
+    }
+
+    fn push(&mut self, particle: Particle) {
+        if self.list.len() < MAX_PARTICLES {
+            self.list.push(particle);
+        }
+    }
+
+    /// Chorro de sangre en `pos` (a media altura) hacia `dir` (rad), p.ej. cuando Jack golpea.
+    pub fn blood_burst(&mut self, pos: Vector2, dir: f32) {
+        for _ in 0..40 {
+            let a = dir + self.range(-1.0, 1.0);
+            let speed = self.range(40.0, 160.0);
+            let life = self.range(0.8, 1.6);
+            let particle = Particle {
+                pos,
+                z: self.range(45.0, 65.0),
+                vel: Vector2::new(a.cos() * speed, a.sin() * speed),
+                vz: self.range(0.0, 90.0),
+                gravity: 300.0,
+                drag: 1.5,
+                life,
+                max_life: life,
+                fade_in: 0.0,
+                size: self.range(2.0, 4.0),
+                key: BLOOD_KEY,
+                emissive: false,
+                sticks: true,
+            };
+            self.push(particle);
+        }
+    }
+
+    /// Lluvia de chispas que cae desde una lámpara del techo en `pos`.
+    pub fn sparks(&mut self, pos: Vector2) {
+        let count = 4 + (self.random() * 6.0) as usize;
+        for _ in 0..count {
+            let a = self.range(0.0, TAU);
+            let speed = self.range(10.0, 60.0);
+            let life = self.range(0.5, 1.1);
+            let particle = Particle {
+                pos,
+                z: WALL_HEIGHT - 6.0,
+                vel: Vector2::new(a.cos() * speed, a.sin() * speed),
+                vz: self.range(-20.0, 30.0),
+                gravity: 220.0,
+                drag: 0.5,
+                life,
+                max_life: life,
+                fade_in: 0.0,
+                size: self.range(1.0, 2.0),
+                key: SPARK_KEY,
+                emissive: true,
+                sticks: false,
+            };
+            self.push(particle);
+        }
+    }
+
+    /// Mantiene unas motas de polvo flotando delante del jugador (solo se ven con la linterna).
+    fn keep_dust(&mut self, player: &Player) {
+        let dust = self.list.iter().filter(|p| p.key == DUST_KEY).count();
+        for _ in dust..DUST_COUNT {
+            let a = player.a + self.range(-0.8, 0.8);
+            let d = self.range(40.0, 350.0);
+            let life = self.range(4.0, 9.0);
+            let particle = Particle {
+                pos: Vector2::new(player.pos.x + a.cos() * d, player.pos.y + a.sin() * d),
+                z: self.range(10.0, 90.0),
+                vel: Vector2::new(self.range(-4.0, 4.0), self.range(-4.0, 4.0)),
+                vz: self.range(-2.0, 2.0),
+                gravity: 0.0,
+                drag: 0.0,
+                life,
+                max_life: life,
+                fade_in: 1.5,
+                size: self.range(0.6, 1.2),
+                key: DUST_KEY,
+                emissive: false,
+                sticks: false,
+            };
+            self.push(particle);
+        }
+    }
+
+    /// Avanza `dt` segundos: mueve y envejece las partículas, repone el polvo y suelta
+    /// chispas de las lámparas rotas cuando se cortan.
+    pub fn update(&mut self, dt: f32, player: &Player, lights: &LightSet, maze: &Maze, block_size: usize) {
+        for particle in self.list.iter_mut() {
+            particle.life -= dt;
+            particle.vz -= particle.gravity * dt;
+            let keep = (1.0 - particle.drag * dt).max(0.0);
+            particle.vel = Vector2::new(particle.vel.x * keep, particle.vel.y * keep);
+
+            let next = Vector2::new(particle.pos.x + particle.vel.x * dt, particle.pos.y + particle.vel.y * dt);
+            if is_wall(maze, next, block_size) {
+                // contra una pared se frena en seco
+                particle.vel = Vector2::new(0.0, 0.0);
+            } else {
+                particle.pos = next;
+            }
+
+            particle.z += particle.vz * dt;
+            if particle.z <= 0.0 {
+                particle.z = 0.0;
+                if particle.sticks {
+                    particle.vel = Vector2::new(0.0, 0.0);
+                    particle.vz = 0.0;
+                    particle.gravity = 0.0;
+                } else {
+                    particle.vz = -particle.vz * 0.4;
+                }
+            }
+        }
+        self.list.retain(|p| p.life > 0.0);
+
+        self.keep_dust(player);
+        // las motas que quedan muy atrás se reciclan antes de tiempo
+        for p in self.list.iter_mut().filter(|p| p.key == DUST_KEY) {
+            let (dx, dy) = (p.pos.x - player.pos.x, p.pos.y - player.pos.y);
+            if dx * dx + dy * dy > 450.0 * 450.0 {
+                p.life = p.life.min(0.5);
+            }
+        }
+
+        let broken: Vec<Vector2> = lights.sparking().map(|l| l.pos).collect();
+        for pos in broken {
+            // mientras está cortada, algunas chispas (no una lluvia cada frame)
+            if self.random() < 4.0 * dt {
+                self.sparks(pos);
+            }
+        }
+    }
+
+    /// Billboards para el renderer de sprites (se ordenan y ocultan con el resto).
+    pub fn sprites(&self) -> impl Iterator<Item = Sprite> + '_ {
+        self.list.iter().map(|p| {
+            let age = p.max_life - p.life;
+            let fade_in = if p.fade_in > 0.0 { (age / p.fade_in).min(1.0) } else { 1.0 };
+            // se desvanece en el último tercio de su vida
+            let fade_out = (p.life / (p.max_life / 3.0)).min(1.0);
+            Sprite {
+                alpha: fade_in * fade_out,
+                emissive: p.emissive,
+                ..Sprite::new(p.pos, p.key).sized(p.size, VerticalAnchor::Height(p.z))
+            }
+        })
+    }
+}
+
+fn is_wall(maze: &Maze, pos: Vector2, block_size: usize) -> bool {
+    if pos.x < 0.0 || pos.y < 0.0 {
+        return true;
+    }
+    let i = pos.x as usize / block_size;
+    let j = pos.y as usize / block_size;
+    match maze.get(j).and_then(|row| row.get(i)) {
+        Some(&c) => !matches!(c, ' ' | 'g' | 's'),
+        None => true,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::f32::consts::PI;
+
+    /// Pasillo recto de 8 celdas con un muro alrededor.
+    fn corridor() -> Maze {
+        ["+--------+", "|        |", "+--------+"].iter().map(|r| r.chars().collect()).collect()
+    }
+
+    fn player() -> Player {
+        Player { pos: Vector2::new(150.0, 150.0), a: 0.0, fov: PI / 2.0 }
+    }
+
+    /// Partícula de sangre quieta a media altura en (x, 150), sin gravedad ni rozamiento.
+    fn particle(x: f32, life: f32) -> Particle {
+        Particle {
+            pos: Vector2::new(x, 150.0),
+            z: 50.0,
+            vel: Vector2::new(0.0, 0.0),
+            vz: 0.0,
+            gravity: 0.0,
+            drag: 0.0,
+            life,
+            max_life: life,
+            fade_in: 0.0,
+            size: 2.0,
+            key: BLOOD_KEY,
+            emissive: false,
+            sticks: true,
+        }
+    }
+
+    fn step(particles: &mut Particles, seconds: f32, dt: f32) {
+        let maze = corridor();
+        for _ in 0..(seconds / dt).round() as usize {
+            particles.update(dt, &player(), &LightSet::empty(), &maze, 100);
+        }
+    }
+
+    fn blood(particles: &Particles) -> Vec<&Particle> {
+        particles.list.iter().filter(|p| p.key != DUST_KEY).collect()
+    }
+
+    #[test]
+    fn particles_expire_when_their_life_runs_out() {
+        let mut particles = Particles::new();
+        particles.push(particle(300.0, 0.5));
+        particles.push(particle(400.0, 1.0));
+        step(&mut particles, 0.3, 0.1);
+        assert_eq!(blood(&particles).len(), 2);
+        step(&mut particles, 0.3, 0.1);
+        assert_eq!(blood(&particles).len(), 1);
+        assert_eq!(blood(&particles)[0].pos.x, 400.0);
+        step(&mut particles, 0.5, 0.1);
+        assert!(blood(&particles).is_empty());
+        // el polvo se repone solo
+        assert_eq!(particles.len(), DUST_COUNT);
+    }
+
+    #[test]
+    fn blood_sticks_to_the_floor_and_sparks_bounce() {
+        let mut particles = Particles::new();
+        particles.push(Particle { gravity: 300.0, ..particle(300.0, 5.0) });
+        particles.push(Particle { gravity: 300.0, key: SPARK_KEY, sticks: false, ..particle(400.0, 5.0) });
+        step(&mut particles, 0.6, 0.01);
+
+        let blood = &particles.list[0];
+        assert_eq!(blood.z, 0.0);
+        assert_eq!((blood.vz, blood.gravity), (0.0, 0.0));
+        // pegada al suelo ya no se mueve
+        step(&mut particles, 0.5, 0.01);
+        assert_eq!(particles.list[0].z, 0.0);
+
+        // la chispa rebotó: volvió a subir, con menos fuerza que la caída
+        let spark = &particles.list[1];
+        assert_eq!(spark.key, SPARK_KEY);
+        let max_z = (0..100)
+            .map(|_| {
+                step(&mut particles, 0.01, 0.01);
+                particles.list[1].z
+            })
+            .fold(0.0, f32::max);
+        assert!(max_z > 0.0 && max_z < 50.0, "rebote hasta {}", max_z);
+    }
+
+    #[test]
+    fn walls_stop_particles() {
+        let mut particles = Particles::new();
+        // a 5 px del muro de la derecha (x = 900), yendo hacia él
+        particles.push(Particle { vel: Vector2::new(200.0, 0.0), ..particle(895.0, 5.0) });
+        step(&mut particles, 0.1, 0.1);
+        let p = &particles.list[0];
+        assert_eq!(p.pos.x, 895.0);
+        assert_eq!((p.vel.x, p.vel.y), (0.0, 0.0));
+    }
+
+    #[test]
+    fn no_more_than_max_particles() {
+        let mut particles = Particles::new();
+        for _ in 0..MAX_PARTICLES {
+            particles.blood_burst(Vector2::new(450.0, 150.0), 0.0);
+        }
+        assert_eq!(particles.len(), MAX_PARTICLES);
+        particles.sparks(Vector2::new(450.0, 150.0));
+        assert_eq!(particles.len(), MAX_PARTICLES);
+    }
+
+    #[test]
+    fn sprites_fade_in_and_out() {
+        let mut particles = Particles::new();
+        let alive = |age: f32| Particle { fade_in: 1.0, life: 3.0 - age, max_life: 3.0, ..particle(300.0, 3.0) };
+        for age in [0.0, 0.5, 1.5, 2.5] {
+            particles.push(alive(age));
+        }
+        let alphas: Vec<f32> = particles.sprites().map(|s| s.alpha).collect();
+        // aparece en el primer segundo y se apaga en el último tercio de su vida (el último segundo)
+        let expected = [0.0, 0.5, 1.0, 0.5];
+        for (alpha, expected) in alphas.iter().zip(expected) {
+            assert!((alpha - expected).abs() < 1e-5, "{:?}", alphas);
+        }
+    }
+}
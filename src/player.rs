@@ -10,28 +10,37 @@ pub struct Player{
 }
 
 pub fn process_events(window: &RaylibHandle, player: &mut Player, maze: &Maze) {
-    const MOVE_SPEED: f32 = 7.0;
-    const ROTATION_SPEED: f32 = PI / 25.0;
-    const TILE_SIZE: f32 = 100.0; // mantén en sincronía con tu main
-
-    let mut new_x = player.pos.x;
-    let mut new_y = player.pos.y;
+    let mut turn = 0.0;
+    let mut forward = 0.0;
 
     if window.is_key_down(KeyboardKey::KEY_LEFT) {
-        player.a -= ROTATION_SPEED;
+        turn -= 1.0;
     }
     if window.is_key_down(KeyboardKey::KEY_RIGHT) {
-        player.a += ROTATION_SPEED;
+        turn += 1.0;
     }
     if window.is_key_down(KeyboardKey::KEY_UP) {
-        new_x = player.pos.x + MOVE_SPEED * player.a.cos();
-        new_y = player.pos.y + MOVE_SPEED * player.a.sin();
+        forward = 1.0;
     }
     if window.is_key_down(KeyboardKey::KEY_DOWN) {
-        new_x = player.pos.x - MOVE_SPEED * player.a.cos();
-        new_y = player.pos.y - MOVE_SPEED * player.a.sin();
+        forward = -1.0;
     }
 
+    move_player(player, maze, forward, turn);
+}
+
+/// Gira (`turn`: -1 izquierda, 1 derecha) y avanza (`forward`: 1 adelante, -1 atrás) al jugador
+/// un paso, sin atravesar paredes. Independiente de dónde venga la entrada (raylib, terminal...).
+pub fn move_player(player: &mut Player, maze: &Maze, forward: f32, turn: f32) {
+    const MOVE_SPEED: f32 = 7.0;
+    const ROTATION_SPEED: f32 = PI / 25.0;
+    const TILE_SIZE: f32 = 100.0; // mantén en sincronía con tu main
+
+    player.a += ROTATION_SPEED * turn;
+
+    let new_x = player.pos.x + forward * MOVE_SPEED * player.a.cos();
+    let new_y = player.pos.y + forward * MOVE_SPEED * player.a.sin();
+
     // calcular índices de celda de forma segura (isize)
     let i_isize = (new_x / TILE_SIZE).floor() as isize;
     let j_isize = (new_y / TILE_SIZE).floor() as isize;
@@ -0,0 +1,255 @@
+use raylib::prelude::*;
+
+/// Etapas del post-proceso, en el orden en que se aplican sobre el frame terminado.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Effect {
+    Shake,               // desplaza toda la imagen unos píxeles
+    ChromaticAberration, // separa rojo y azul hacia los bordes
+    Desaturate,          // mezcla hacia gris
+    DamageFlash,         // tinte rojo, más fuerte en los bordes
+    Scanlines,           // oscurece una de cada dos filas (CRT)
+    Grain,               // ruido de película, distinto cada frame
+}
+
+/// Una etapa de la cadena: `strength` (0..1) la pone el juego cada frame;
+/// `max` es cuánto efecto hay con strength = 1 (px, fracción de mezcla, etc. según la etapa).
+pub struct Stage {
+    pub effect: Effect,
+    pub enabled: bool,
+    pub strength: f32,
+    pub max: f32,
+}
+
+/// Cadena de efectos de post-proceso (ver `Framebuffer::apply_effects`).
+pub struct PostFx {
+    stages: Vec<Stage>,
+    // px de la imagen por px de la ventana (las etapas en px se achican con la resolución interna)
+    pixel_scale: f32,
+}
+
+impl PostFx {
+    /// Cadena completa, todas las etapas activas y con fuerza 0.
+    pub fn new() -> Self {
+        let stage = |effect, max| Stage { effect, enabled: true, strength: 0.0, max };
+        PostFx {
+            stages: vec![
+                stage(Effect::Shake, 12.0),
+                stage(Effect::ChromaticAberration, 6.0),
+                stage(Effect::Desaturate, 0.85),
+                stage(Effect::DamageFlash, 0.55),
+                stage(Effect::Scanlines, 0.18),
+                stage(Effect::Grain, 0.12),
+            ],
+            pixel_scale: 1.0,
+        }
+    }
+
+    pub fn stage_mut(&mut self, effect: Effect) -> &mut Stage {
+        self.stages
+            .iter_mut()
+            .find(|s| s.effect == effect)
+            .expect("todas las etapas existen desde PostFx::new")
+    }
+
+    pub fn set_enabled(&mut self, effect: Effect, enabled: bool) {
+        self.stage_mut(effect).enabled = enabled;
+    }
+
+    pub fn set_strength(&mut self, effect: Effect, strength: f32) {
+        self.stage_mut(effect).strength = strength.clamp(0.0, 1.0);
+    }
+
+    /// Para imágenes a otra resolución que la ventana: `scale` = ancho de la imagen / ancho de
+    /// la ventana. El temblor y la aberración (en px) se ajustan para verse igual al estirarla.
+    pub fn set_pixel_scale(&mut self, scale: f32) {
+        self.pixel_scale = scale.max(0.0);
+    }
+
+    /// Aplica las etapas activas (con fuerza > 0) a `pixels` (fila por fila, `w` x `h`).
+    /// `time` (s) mueve el ruido y el temblor.
+    pub fn apply(&self, pixels: &mut [Color], w: usize, h: usize, time: f32) {
+        if w == 0 || h == 0 {
+            return;
+        }
+        let seed = (time * 60.0) as u32;
+        for stage in self.stages.iter().filter(|s| s.enabled && s.strength > 0.001) {
+            let amount = stage.max * stage.strength;
+            match stage.effect {
+                Effect::Shake => shake(pixels, w, h, amount * self.pixel_scale, time),
+                Effect::ChromaticAberration => chromatic_aberration(pixels, w, h, amount * self.pixel_scale),
+                Effect::Desaturate => desaturate(pixels, amount),
+                Effect::DamageFlash => damage_flash(pixels, w, h, amount),
+                Effect::Scanlines => scanlines(pixels, w, amount),
+                Effect::Grain => grain(pixels, w, amount, seed),
+            }
+        }
+    }
+}
+
+fn shake(pixels: &mut [Color], w: usize, h: usize, amount: f32, time: f32) {
+    // dos senos de frecuencias distintas por eje: temblor irregular pero continuo
+    let dx = (amount * ((time * 53.0).sin() * 0.6 + (time * 31.0).sin() * 0.4)).round() as isize;
+    let dy = (amount * ((time * 47.0).cos() * 0.6 + (time * 29.0).sin() * 0.4)).round() as isize;
+    if dx == 0 && dy == 0 {
+        return;
+    }
+    let src = pixels.to_vec();
+    for y in 0..h {
+        let sy = (y as isize - dy).clamp(0, h as isize - 1) as usize;
+        for x in 0..w {
+            let sx = (x as isize - dx).clamp(0, w as isize - 1) as usize;
+            pixels[y * w + x] = src[sy * w + sx];
+        }
+    }
+}
+
+fn chromatic_aberration(pixels: &mut [Color], w: usize, h: usize, amount: f32) {
+    let src = pixels.to_vec();
+    let cx = w as f32 / 2.0;
+    for x in 0..w {
+        // 0 en el centro, `amount` px en los bordes
+        let off = (amount * (x as f32 - cx) / cx).round() as isize;
+        if off == 0 {
+            continue;
+        }
+        let xr = (x as isize + off).clamp(0, w as isize - 1) as usize;
+        let xb = (x as isize - off).clamp(0, w as isize - 1) as usize;
+        for y in 0..h {
+            let row = y * w;
+            pixels[row + x].r = src[row + xr].r;
+            pixels[row + x].b = src[row + xb].b;
+        }
+    }
+}
+
+fn desaturate(pixels: &mut [Color], amount: f32) {
+    let k = amount.min(1.0);
+    for p in pixels.iter_mut() {
+        let luma = 0.299 * p.r as f32 + 0.587 * p.g as f32 + 0.114 * p.b as f32;
+        let mix = |c: u8| (c as f32 + (luma - c as f32) * k) as u8;
+        *p = Color::new(mix(p.r), mix(p.g), mix(p.b), p.a);
+    }
+}
+
+fn damage_flash(pixels: &mut [Color], w: usize, h: usize, amount: f32) {
+    let (cx, cy) = (w as f32 / 2.0, h as f32 / 2.0);
+    for y in 0..h {
+        let ny = (y as f32 - cy) / cy;
+        for x in 0..w {
+            let nx = (x as f32 - cx) / cx;
+            // algo de rojo en el centro y el doble hacia las esquinas
+            let a = (amount * (0.5 + 0.5 * (nx * nx + ny * ny))).min(1.0);
+            let p = &mut pixels[y * w + x];
+            p.r = (p.r as f32 + (200.0 - p.r as f32) * a) as u8;
+            p.g = (p.g as f32 * (1.0 - a)) as u8;
+            p.b = (p.b as f32 * (1.0 - a)) as u8;
+        }
+    }
+}
+
+fn scanlines(pixels: &mut [Color], w: usize, amount: f32) {
+    let k = 1.0 - amount.min(1.0);
+    for row in pixels.chunks_mut(w).skip(1).step_by(2) {
+        for p in row.iter_mut() {
+            *p = Color::new((p.r as f32 * k) as u8, (p.g as f32 * k) as u8, (p.b as f32 * k) as u8, p.a);
+        }
+    }
+}
+
+fn grain(pixels: &mut [Color], w: usize, amount: f32, seed: u32) {
+    let range = amount * 255.0;
+    for (i, p) in pixels.iter_mut().enumerate() {
+        let (x, y) = ((i % w) as u32, (i / w) as u32);
+        // mismo ruido para los tres canales: grano gris, no de colores
+        let n = (hash(x, y, seed) as f32 / u32::MAX as f32 - 0.5) * 2.0 * range;
+        let add = |c: u8| (c as f32 + n).clamp(0.0, 255.0) as u8;
+        *p = Color::new(add(p.r), add(p.g), add(p.b), p.a);
+    }
+}
+
+/// Hash entero barato para el ruido (x, y, frame).
+pub fn hash(x: u32, y: u32, seed: u32) -> u32 {
+    let mut h = x.wrapping_mul(0x8da6_b343) ^ y.wrapping_mul(0xd816_3841) ^ seed.wrapping_mul(0xcb1a_b31f);
+    h ^= h >> 13;
+    h = h.wrapping_mul(0x5bd1_e995);
+    h ^ (h >> 15)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const W: usize = 16;
+    const H: usize = 8;
+    const ALL: [Effect; 6] = [
+        Effect::Shake,
+        Effect::ChromaticAberration,
+        Effect::Desaturate,
+        Effect::DamageFlash,
+        Effect::Scanlines,
+        Effect::Grain,
+    ];
+
+    /// Cada píxel distinto: rojo según la columna, verde según la fila, azul al revés que el rojo.
+    fn image() -> Vec<Color> {
+        (0..W * H)
+            .map(|i| {
+                let (x, y) = ((i % W) as u8, (i / W) as u8);
+                Color::new(x * 10, y * 10, 255 - x * 10, 255)
+            })
+            .collect()
+    }
+
+    #[test]
+    fn no_strength_or_disabled_leaves_the_image_alone() {
+        let mut pixels = image();
+        PostFx::new().apply(&mut pixels, W, H, 0.3);
+        assert!(pixels == image());
+
+        let mut fx = PostFx::new();
+        for effect in ALL {
+            fx.set_strength(effect, 1.0);
+            fx.set_enabled(effect, false);
+        }
+        fx.apply(&mut pixels, W, H, 0.3);
+        assert!(pixels == image());
+
+        // sin px de imagen por px de ventana, el temblor y la aberración no mueven nada
+        let mut fx = PostFx::new();
+        fx.set_strength(Effect::Shake, 1.0);
+        fx.set_strength(Effect::ChromaticAberration, 1.0);
+        fx.set_pixel_scale(0.0);
+        fx.apply(&mut pixels, W, H, 0.3);
+        assert!(pixels == image());
+    }
+
+    #[test]
+    fn shake_past_the_edges_repeats_the_border() {
+        let src = image();
+        let mut pixels = src.clone();
+        // cientos de px de temblor en una imagen de 16x8: todo sale de una esquina
+        shake(&mut pixels, W, H, 1200.0, 0.3);
+        let corners = [src[0], src[W - 1], src[(H - 1) * W], src[H * W - 1]];
+        assert!(corners.contains(&pixels[0]));
+        assert!(pixels.iter().all(|&p| p == pixels[0]));
+    }
+
+    #[test]
+    fn chromatic_aberration_clamps_at_the_edges() {
+        let src = image();
+        let mut pixels = src.clone();
+        chromatic_aberration(&mut pixels, W, H, 1000.0);
+        for y in 0..H {
+            let row = y * W;
+            // en el borde izquierdo el rojo no puede venir de más a la izquierda; el azul, del otro borde
+            assert_eq!(pixels[row].r, src[row].r);
+            assert_eq!(pixels[row].b, src[row + W - 1].b);
+            assert_eq!(pixels[row + W - 1].r, src[row + W - 1].r);
+            assert_eq!(pixels[row + W - 1].b, src[row].b);
+            // el verde y el alpha no se tocan
+            for x in 0..W {
+                assert_eq!((pixels[row + x].g, pixels[row + x].a), (src[row + x].g, src[row + x].a));
+            }
+        }
+    }
+}
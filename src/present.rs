@@ -0,0 +1,115 @@
+use raylib::prelude::*;
+use std::ops::{Deref, DerefMut};
+
+use crate::framebuffer::Framebuffer;
+
+/// Muestra un frame terminado del framebuffer en algún destino.
+/// El dibujo (escena, HUD, texto) lo hace `Framebuffer` en CPU; el presentador solo lo entrega.
+pub trait Presenter {
+    fn present(&mut self, frame: &Framebuffer);
+
+    /// Muestra `scene` estirada al tamaño de `overlay` y `overlay` encima (transparente donde
+    /// se ve la escena). Por defecto se juntan en CPU y se presenta el resultado.
+    fn present_layers(&mut self, scene: &Framebuffer, overlay: &Framebuffer) {
+        self.present(&Framebuffer::composed(scene, overlay));
+    }
+}
+
+/// Presentador de la ventana de raylib: sube los píxeles a una textura y la dibuja.
+/// Con capas, la escena va en su propia textura y la GPU la estira al dibujarla.
+/// Da acceso al `RaylibHandle` para leer teclado, fps, etc.
+pub struct RaylibPresenter {
+    pub rl: RaylibHandle,
+    pub thread: RaylibThread,
+    texture: Option<Texture2D>,
+    scene_texture: Option<Texture2D>,
+}
+
+impl RaylibPresenter {
+    pub fn new(rl: RaylibHandle, thread: RaylibThread) -> Self {
+        RaylibPresenter { rl, thread, texture: None, scene_texture: None }
+    }
+}
+
+impl Deref for RaylibPresenter {
+    type Target = RaylibHandle;
+    fn deref(&self) -> &RaylibHandle {
+        &self.rl
+    }
+}
+
+impl DerefMut for RaylibPresenter {
+    fn deref_mut(&mut self) -> &mut RaylibHandle {
+        &mut self.rl
+    }
+}
+
+/// Sube `frame` a `slot`, creando la textura (o recreándola si cambió el tamaño).
+fn upload<'a>(
+    rl: &mut RaylibHandle,
+    thread: &RaylibThread,
+    slot: &'a mut Option<Texture2D>,
+    frame: &Framebuffer,
+) -> &'a Texture2D {
+    let stale = slot
+        .as_ref()
+        .map_or(true, |t| t.width() != frame.width || t.height() != frame.height);
+    if stale {
+        let image = Image::gen_image_color(frame.width, frame.height, Color::BLACK);
+        let texture = rl
+            .load_texture_from_image(thread, &image)
+            .expect("Failed to create reuse_texture");
+        *slot = Some(texture);
+    }
+    let texture = slot.as_mut().expect("la textura se acaba de crear");
+    texture.update_texture(frame.pixel_bytes()).unwrap();
+    texture
+}
+
+impl Presenter for RaylibPresenter {
+    fn present(&mut self, frame: &Framebuffer) {
+        let texture = upload(&mut self.rl, &self.thread, &mut self.texture, frame);
+
+        let mut d = self.rl.begin_drawing(&self.thread);
+        d.draw_texture(texture, 0, 0, Color::WHITE);
+    }
+
+    fn present_layers(&mut self, scene: &Framebuffer, overlay: &Framebuffer) {
+        let scene_texture = upload(&mut self.rl, &self.thread, &mut self.scene_texture, scene);
+        let texture = upload(&mut self.rl, &self.thread, &mut self.texture, overlay);
+
+        let mut d = self.rl.begin_drawing(&self.thread);
+        // la escena a resolución interna, estirada a toda la ventana (filtro de la textura: vecino más cercano)
+        let source = Rectangle::new(0.0, 0.0, scene.width as f32, scene.height as f32);
+        let dest = Rectangle::new(0.0, 0.0, overlay.width as f32, overlay.height as f32);
+        d.draw_texture_pro(scene_texture, source, dest, Vector2::new(0.0, 0.0), 0.0, Color::WHITE);
+        // el HUD a resolución completa, mezclado por su alpha
+        d.draw_texture(texture, 0, 0, Color::WHITE);
+    }
+}
+
+/// Presentador sin ventana: guarda una copia del último frame y, si tiene ruta de salida,
+/// escribe cada frame a PNG (`{n}` en la ruta se reemplaza por el número de frame).
+pub struct HeadlessPresenter {
+    output: Option<String>,
+    pub frames: usize,
+    pub last_frame: Vec<Color>,
+}
+
+impl HeadlessPresenter {
+    pub fn new(output: Option<String>) -> Self {
+        HeadlessPresenter { output, frames: 0, last_frame: Vec::new() }
+    }
+}
+
+impl Presenter for HeadlessPresenter {
+    fn present(&mut self, frame: &Framebuffer) {
+        self.last_frame.clear();
+        self.last_frame.extend_from_slice(frame.pixels());
+        if let Some(output) = &self.output {
+            let path = output.replace("{n}", &format!("{:05}", self.frames));
+            frame.render_to_file(&path);
+        }
+        self.frames += 1;
+    }
+}
@@ -0,0 +1,300 @@
+use raylib::prelude::*;
+
+use crate::postfx::hash;
+
+/// Dibujos que se saben generar.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Pattern {
+    Bricks, // ladrillos en hileras desfasadas, cada uno con su tono
+    Tiles,  // baldosas cuadradas con junta y un poco de relieve
+    Noise,  // ruido suave entre `base` y `accent`
+    Stains, // manchas de `accent` sobre `base`
+    Grime,  // mugre: manchas de `accent` sobre transparente (para poner encima de otra cosa)
+    Blob,   // mancha redonda de `accent` que se desvanece hacia el borde (sombras, brillos)
+}
+
+/// Receta de una textura generada: con la misma receta sale siempre la misma imagen.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct Recipe {
+    pub pattern: Pattern,
+    pub seed: u32,
+    pub size: i32,     // ancho y alto en px (la textura es cuadrada y se repite sin costuras)
+    pub base: Color,   // color principal (ladrillo, baldosa, fondo)
+    pub accent: Color, // color secundario (junta, manchas)
+    pub scale: f32,    // tamaño del detalle: ladrillos/baldosas por lado, o grosor del ruido
+}
+
+impl Pattern {
+    pub fn parse(name: &str) -> Option<Pattern> {
+        match name {
+            "bricks" => Some(Pattern::Bricks),
+            "tiles" => Some(Pattern::Tiles),
+            "noise" => Some(Pattern::Noise),
+            "stains" => Some(Pattern::Stains),
+            "grime" => Some(Pattern::Grime),
+            "blob" => Some(Pattern::Blob),
+            _ => None,
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Pattern::Bricks => "bricks",
+            Pattern::Tiles => "tiles",
+            Pattern::Noise => "noise",
+            Pattern::Stains => "stains",
+            Pattern::Grime => "grime",
+            Pattern::Blob => "blob",
+        }
+    }
+}
+
+impl Recipe {
+    /// Receta con los colores y la escala por defecto del dibujo.
+    pub fn new(pattern: Pattern, seed: u32) -> Self {
+        let (base, accent, scale) = match pattern {
+            Pattern::Bricks => (Color::new(140, 62, 45, 255), Color::new(120, 115, 105, 255), 4.0),
+            Pattern::Tiles => (Color::new(185, 185, 175, 255), Color::new(70, 70, 65, 255), 4.0),
+            Pattern::Noise => (Color::new(90, 90, 90, 255), Color::new(150, 150, 150, 255), 8.0),
+            Pattern::Stains => (Color::new(150, 140, 120, 255), Color::new(75, 60, 40, 255), 6.0),
+            Pattern::Grime => (Color::new(0, 0, 0, 0), Color::new(35, 30, 20, 200), 6.0),
+            Pattern::Blob => (Color::new(0, 0, 0, 0), Color::new(0, 0, 0, 180), 4.0),
+        };
+        Recipe { pattern, seed, size: 64, base, accent, scale }
+    }
+
+    /// Ajusta un parámetro `nombre=valor` (size, base, accent, scale). Devuelve false si no es válido.
+    pub fn set(&mut self, name: &str, value: &str) -> bool {
+        match name {
+            "size" => match value.parse::<i32>() {
+                Ok(size) if (4..=1024).contains(&size) => self.size = size,
+                _ => return false,
+            },
+            "scale" => match value.parse::<f32>() {
+                Ok(scale) if scale > 0.0 => self.scale = scale,
+                _ => return false,
+            },
+            "base" | "accent" => {
+                let Some(color) = parse_color(value) else {
+                    return false;
+                };
+                if name == "base" {
+                    self.base = color;
+                } else {
+                    self.accent = color;
+                }
+            }
+            _ => return false,
+        }
+        true
+    }
+
+    pub fn describe(&self) -> String {
+        format!("{} semilla {} (generada)", self.pattern.name(), self.seed)
+    }
+
+    /// Genera los colores (fila por fila) de una imagen de `size` x `size`.
+    pub fn generate(&self) -> (Vec<Color>, i32, i32) {
+        let size = self.size.max(1);
+        let mut colors = Vec::with_capacity((size * size) as usize);
+        for y in 0..size {
+            for x in 0..size {
+                colors.push(self.pixel(x, y));
+            }
+        }
+        (colors, size, size)
+    }
+
+    fn pixel(&self, x: i32, y: i32) -> Color {
+        let size = self.size.max(1);
+        let (u, v) = (x as f32 / size as f32, y as f32 / size as f32);
+        // el ruido se repite con la textura: su periodo es `scale` celdas por lado
+        let cells = (self.scale.round() as i32).clamp(1, size);
+        let grain = fbm(u, v, cells * 2, self.seed);
+
+        match self.pattern {
+            Pattern::Bricks => {
+                let rows = cells * 2;
+                let row = (v * rows as f32) as i32;
+                // hileras impares desfasadas medio ladrillo
+                let shift = if row % 2 == 1 { 0.5 } else { 0.0 };
+                let bu = u * cells as f32 + shift;
+                let column = bu.floor() as i32;
+                let (fu, fv) = (bu.fract(), (v * rows as f32).fract());
+                let mortar = 1.5 / (size as f32 / cells as f32);
+                if fu < mortar || fv < mortar * 2.0 {
+                    return shade(self.accent, 0.85 + 0.3 * grain);
+                }
+                // cada ladrillo con su propio tono
+                let id = hash(column.rem_euclid(cells) as u32, row as u32, self.seed);
+                let tone = 0.8 + 0.3 * unit(id);
+                shade(self.base, tone * (0.85 + 0.3 * grain))
+            }
+            Pattern::Tiles => {
+                let (tu, tv) = (u * cells as f32, v * cells as f32);
+                let (fu, fv) = (tu.fract(), tv.fract());
+                let grout = 1.0 / (size as f32 / cells as f32);
+                if fu < grout || fv < grout {
+                    return shade(self.accent, 0.9 + 0.2 * grain);
+                }
+                let id = hash(tu as u32, tv as u32, self.seed);
+                // relieve: borde de arriba/izquierda claro, de abajo/derecha oscuro
+                let edge = grout * 2.0;
+                let bevel = if fu < edge || fv < edge {
+                    1.15
+                } else if fu > 1.0 - edge || fv > 1.0 - edge {
+                    0.8
+                } else {
+                    1.0
+                };
+                shade(self.base, bevel * (0.92 + 0.08 * unit(id)) * (0.95 + 0.1 * grain))
+            }
+            Pattern::Noise => mix(self.base, self.accent, grain),
+            Pattern::Stains => {
+                let blot = smoothstep(0.55, 0.7, fbm(u, v, cells, self.seed ^ 0x5157));
+                mix(shade(self.base, 0.9 + 0.2 * grain), self.accent, blot * 0.8)
+            }
+            Pattern::Blob => {
+                // caída suave desde el centro, con el borde un poco irregular
+                let (dx, dy) = (u * 2.0 - 1.0, v * 2.0 - 1.0);
+                let r = (dx * dx + dy * dy).sqrt() * (0.9 + 0.2 * grain);
+                let fade = (1.0 - r * r).max(0.0);
+                Color::new(self.accent.r, self.accent.g, self.accent.b, (self.accent.a as f32 * fade * fade) as u8)
+            }
+            Pattern::Grime => {
+                let blot = smoothstep(0.5, 0.75, fbm(u, v, cells, self.seed ^ 0x6d1e));
+                let a = (self.accent.a as f32 * blot * (0.7 + 0.3 * grain)) as u8;
+                Color::new(self.accent.r, self.accent.g, self.accent.b, a)
+            }
+        }
+    }
+}
+
+/// "R,G,B" o "R,G,B,A".
+pub fn parse_color(value: &str) -> Option<Color> {
+    let parts: Vec<u8> = value.split(',').map(|n| n.parse().ok()).collect::<Option<_>>()?;
+    match parts[..] {
+        [r, g, b] => Some(Color::new(r, g, b, 255)),
+        [r, g, b, a] => Some(Color::new(r, g, b, a)),
+        _ => None,
+    }
+}
+
+/// Hash a 0..1.
+fn unit(h: u32) -> f32 {
+    h as f32 / u32::MAX as f32
+}
+
+/// Ruido de valor en (u, v) (0..1), con `period` celdas por lado y repetible en los bordes.
+fn value_noise(u: f32, v: f32, period: i32, seed: u32) -> f32 {
+    let period = period.max(1);
+    let (x, y) = (u * period as f32, v * period as f32);
+    let (x0, y0) = (x.floor() as i32, y.floor() as i32);
+    let (fx, fy) = (x - x0 as f32, y - y0 as f32);
+    let corner = |cx: i32, cy: i32| unit(hash(cx.rem_euclid(period) as u32, cy.rem_euclid(period) as u32, seed));
+    let (sx, sy) = (fx * fx * (3.0 - 2.0 * fx), fy * fy * (3.0 - 2.0 * fy));
+    let top = corner(x0, y0) + (corner(x0 + 1, y0) - corner(x0, y0)) * sx;
+    let bottom = corner(x0, y0 + 1) + (corner(x0 + 1, y0 + 1) - corner(x0, y0 + 1)) * sx;
+    top + (bottom - top) * sy
+}
+
+/// Cuatro octavas de ruido de valor (cada una con el doble de detalle y la mitad de peso).
+fn fbm(u: f32, v: f32, period: i32, seed: u32) -> f32 {
+    let (mut total, mut weight, mut norm) = (0.0, 1.0, 0.0);
+    for octave in 0..4 {
+        total += value_noise(u, v, period << octave, seed.wrapping_add(octave)) * weight;
+        norm += weight;
+        weight *= 0.5;
+    }
+    total / norm
+}
+
+fn smoothstep(edge0: f32, edge1: f32, x: f32) -> f32 {
+    let t = ((x - edge0) / (edge1 - edge0)).clamp(0.0, 1.0);
+    t * t * (3.0 - 2.0 * t)
+}
+
+fn shade(color: Color, k: f32) -> Color {
+    let f = |c: u8| (c as f32 * k).clamp(0.0, 255.0) as u8;
+    Color::new(f(color.r), f(color.g), f(color.b), color.a)
+}
+
+fn mix(a: Color, b: Color, t: f32) -> Color {
+    let t = t.clamp(0.0, 1.0);
+    let f = |x: u8, y: u8| (x as f32 + (y as f32 - x as f32) * t) as u8;
+    Color::new(f(a.r, b.r), f(a.g, b.g), f(a.b, b.b), f(a.a, b.a))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ALL: [Pattern; 6] = [Pattern::Bricks, Pattern::Tiles, Pattern::Noise, Pattern::Stains, Pattern::Grime, Pattern::Blob];
+
+    #[test]
+    fn same_recipe_same_image() {
+        for pattern in ALL {
+            let recipe = Recipe::new(pattern, 42);
+            let (a, w, h) = recipe.generate();
+            assert_eq!((w, h), (64, 64));
+            assert_eq!(a.len(), 64 * 64);
+            assert_eq!(a, recipe.generate().0, "{} no es determinista", pattern.name());
+        }
+    }
+
+    #[test]
+    fn seed_changes_the_image() {
+        for pattern in ALL {
+            let a = Recipe::new(pattern, 1).generate().0;
+            let b = Recipe::new(pattern, 2).generate().0;
+            assert_ne!(a, b, "{} ignora la semilla", pattern.name());
+        }
+    }
+
+    #[test]
+    fn noise_tiles_seamlessly() {
+        for seed in 0..4 {
+            for i in 0..32 {
+                let v = i as f32 / 32.0;
+                assert!((value_noise(0.0, v, 6, seed) - value_noise(1.0, v, 6, seed)).abs() < 1e-5);
+                assert!((fbm(v, 0.0, 4, seed) - fbm(v, 1.0, 4, seed)).abs() < 1e-5);
+            }
+        }
+    }
+
+    #[test]
+    fn blob_fades_from_center_to_edge() {
+        let recipe = Recipe { accent: Color::new(255, 255, 255, 200), ..Recipe::new(Pattern::Blob, 1) };
+        let (colors, w, _) = recipe.generate();
+        let alpha = |x: i32, y: i32| colors[(y * w + x) as usize].a;
+        assert!(alpha(32, 32) > 150);
+        assert_eq!(alpha(0, 0), 0);
+        assert!(alpha(32, 32) > alpha(32, 48) && alpha(32, 48) > alpha(32, 62));
+    }
+
+    #[test]
+    fn set_accepts_only_valid_values() {
+        let mut recipe = Recipe::new(Pattern::Bricks, 1);
+        assert!(recipe.set("size", "16"));
+        assert!(recipe.set("scale", "2.5"));
+        assert!(recipe.set("base", "1,2,3"));
+        assert!(recipe.set("accent", "4,5,6,7"));
+        assert_eq!((recipe.size, recipe.scale), (16, 2.5));
+        assert_eq!((recipe.base, recipe.accent), (Color::new(1, 2, 3, 255), Color::new(4, 5, 6, 7)));
+        assert_eq!(recipe.generate().0.len(), 16 * 16);
+
+        for (name, value) in [("size", "2"), ("size", "x"), ("scale", "0"), ("base", "1,2"), ("base", "1,2,300"), ("seed", "3")] {
+            assert!(!recipe.set(name, value), "{}={} no debería valer", name, value);
+        }
+        assert_eq!(recipe.size, 16);
+    }
+
+    #[test]
+    fn pattern_names_round_trip() {
+        for pattern in ALL {
+            assert_eq!(Pattern::parse(pattern.name()), Some(pattern));
+        }
+        assert_eq!(Pattern::parse("marble"), None);
+        assert_eq!(parse_color("1,2,3,4,5"), None);
+    }
+}
@@ -0,0 +1,76 @@
+use std::time::Duration;
+
+// Pasos de la escala interna y límite inferior en modo dinámico
+const SCALE_STEP: f32 = 0.125;
+const MIN_SCALE: f32 = 0.5;
+// Cuántos frames seguidos fuera / dentro de presupuesto antes de cambiar de escala
+const FRAMES_TO_LOWER: u32 = 10;
+const FRAMES_TO_RAISE: u32 = 90;
+
+/// Escala de la resolución interna de la vista 3D (1.0 = resolución de la ventana).
+/// En modo dinámico baja cuando el trabajo del frame pasa del presupuesto y vuelve a
+/// subir (hasta la escala configurada) cuando sobra tiempo.
+pub struct RenderScale {
+    scale: f32,
+    max_scale: f32,
+    dynamic: bool,
+    budget: Duration,
+    over_frames: u32,
+    under_frames: u32,
+}
+
+impl RenderScale {
+    pub fn new(scale: f32, dynamic: bool, budget: Duration) -> Self {
+        let scale = scale.clamp(0.25, 1.0);
+        RenderScale {
+            scale,
+            max_scale: scale,
+            dynamic,
+            budget,
+            over_frames: 0,
+            under_frames: 0,
+        }
+    }
+
+    pub fn scale(&self) -> f32 {
+        self.scale
+    }
+
+    /// Tamaño interno para una ventana de `width` x `height`.
+    pub fn scaled_size(&self, width: i32, height: i32) -> (i32, i32) {
+        (
+            ((width as f32 * self.scale).round() as i32).max(1),
+            ((height as f32 * self.scale).round() as i32).max(1),
+        )
+    }
+
+    /// Registra lo que tardó el trabajo del frame (sin contar la espera de vsync/fps).
+    /// Devuelve true si la escala cambió.
+    pub fn update(&mut self, work_time: Duration) -> bool {
+        if !self.dynamic {
+            return false;
+        }
+
+        if work_time > self.budget {
+            self.over_frames += 1;
+            self.under_frames = 0;
+        } else if work_time.as_secs_f32() < self.budget.as_secs_f32() * 0.6 {
+            self.under_frames += 1;
+            self.over_frames = 0;
+        } else {
+            self.over_frames = 0;
+            self.under_frames = 0;
+        }
+
+        let old = self.scale;
+        let floor = MIN_SCALE.min(self.max_scale);
+        if self.over_frames >= FRAMES_TO_LOWER {
+            self.scale = (self.scale - SCALE_STEP).max(floor);
+            self.over_frames = 0;
+        } else if self.under_frames >= FRAMES_TO_RAISE {
+            self.scale = (self.scale + SCALE_STEP).min(self.max_scale);
+            self.under_frames = 0;
+        }
+        self.scale != old
+    }
+}
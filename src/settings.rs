@@ -0,0 +1,123 @@
+use std::fs;
+
+/// Ajustes del juego, leídos de `settings.txt` (líneas `clave = valor`, '#' = comentario).
+/// Si el archivo no existe o falta una clave, se usa el valor por defecto.
+pub struct Settings {
+    pub mipmaps: bool,  // elegir nivel de mipmap según el tamaño en pantalla
+    pub bilinear: bool, // filtrado bilineal de texturas (más suave, más costoso)
+    pub render_scale: f32,        // resolución interna de la vista 3D (0.25..1.0 de la ventana)
+    pub dynamic_resolution: bool, // bajar la escala sola si el frame no llega a tiempo
+    pub render_threads: usize,    // hilos para dibujar la vista 3D (0 = según los núcleos)
+    pub palette: bool, // sombrear con tablas sobre una paleta de 256 colores (más rápido)
+    pub retro: bool,   // mostrar el resultado en la paleta, estilo 8 bits (implica `palette`)
+    pub sprite_near: f32, // distancia mínima a la que se dibujan los sprites
+    pub sprite_far: f32,  // y la máxima (Jack al fondo de un pasillo largo)
+    pub particles: bool,  // polvo, chispas y sangre
+    // post-proceso (cada etapa se puede apagar; su fuerza la decide el juego)
+    pub grain: bool,
+    pub scanlines: bool,
+    pub chromatic_aberration: bool,
+    pub desaturate: bool,
+    pub damage_flash: bool,
+    pub screen_shake: bool,
+    pub capture_every: usize, // al grabar (F10), guardar 1 de cada N frames
+    pub capture_gif: bool,    // grabar a un GIF animado en lugar de a PNG numerados
+    // piezas del HUD que se muestran
+    pub hud_fps: bool,
+    pub hud_floor: bool,
+    pub hud_health: bool,
+    pub hud_minimap: bool,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Settings {
+            mipmaps: true,
+            bilinear: false,
+            render_scale: 1.0,
+            dynamic_resolution: true,
+            render_threads: 0,
+            palette: false,
+            retro: false,
+            sprite_near: 20.0,
+            sprite_far: 4000.0,
+            particles: true,
+            grain: true,
+            scanlines: false,
+            chromatic_aberration: true,
+            desaturate: true,
+            damage_flash: true,
+            screen_shake: true,
+            capture_every: 2,
+            capture_gif: false,
+            hud_fps: true,
+            hud_floor: true,
+            hud_health: true,
+            hud_minimap: true,
+        }
+    }
+}
+
+impl Settings {
+    pub fn load(filename: &str) -> Self {
+        let mut settings = Settings::default();
+
+        let Ok(text) = fs::read_to_string(filename) else {
+            return settings;
+        };
+
+        for (n, line) in text.lines().enumerate() {
+            let line = line.split('#').next().unwrap_or("").trim();
+            if line.is_empty() {
+                continue;
+            }
+            let Some((key, value)) = line.split_once('=') else {
+                eprintln!("{}:{}: se esperaba `clave = valor`: {}", filename, n + 1, line);
+                continue;
+            };
+            let (key, value) = (key.trim(), value.trim());
+
+            let ok = match key {
+                "mipmaps" => parse_bool(value).map(|v| settings.mipmaps = v),
+                "bilinear" => parse_bool(value).map(|v| settings.bilinear = v),
+                "render_scale" => value.parse::<f32>().ok().map(|v| settings.render_scale = v),
+                "dynamic_resolution" => parse_bool(value).map(|v| settings.dynamic_resolution = v),
+                "render_threads" => value.parse::<usize>().ok().map(|v| settings.render_threads = v),
+                "palette" => parse_bool(value).map(|v| settings.palette = v),
+                "retro" => parse_bool(value).map(|v| settings.retro = v),
+                "sprite_near" => value.parse::<f32>().ok().map(|v| settings.sprite_near = v),
+                "sprite_far" => value.parse::<f32>().ok().map(|v| settings.sprite_far = v),
+                "particles" => parse_bool(value).map(|v| settings.particles = v),
+                "grain" => parse_bool(value).map(|v| settings.grain = v),
+                "scanlines" => parse_bool(value).map(|v| settings.scanlines = v),
+                "chromatic_aberration" => parse_bool(value).map(|v| settings.chromatic_aberration = v),
+                "desaturate" => parse_bool(value).map(|v| settings.desaturate = v),
+                "damage_flash" => parse_bool(value).map(|v| settings.damage_flash = v),
+                "screen_shake" => parse_bool(value).map(|v| settings.screen_shake = v),
+                "hud_fps" => parse_bool(value).map(|v| settings.hud_fps = v),
+                "hud_floor" => parse_bool(value).map(|v| settings.hud_floor = v),
+                "hud_health" => parse_bool(value).map(|v| settings.hud_health = v),
+                "hud_minimap" => parse_bool(value).map(|v| settings.hud_minimap = v),
+                "capture_gif" => parse_bool(value).map(|v| settings.capture_gif = v),
+                "capture_every" => value.parse::<usize>().ok().filter(|&v| v > 0).map(|v| settings.capture_every = v),
+                _ => {
+                    eprintln!("{}:{}: ajuste desconocido: {}", filename, n + 1, key);
+                    continue;
+                }
+            };
+            if ok.is_none() {
+                eprintln!("{}:{}: valor inválido para {}: {}", filename, n + 1, key, value);
+            }
+        }
+
+        settings
+    }
+}
+
+fn parse_bool(value: &str) -> Option<bool> {
+    match value {
+        "true" | "on" | "1" => Some(true),
+        "false" | "off" | "0" => Some(false),
+        _ => None,
+    }
+}
@@ -0,0 +1,323 @@
+use raylib::prelude::*;
+use std::f32::consts::PI;
+
+use crate::framebuffer::Framebuffer;
+use crate::light::{shade_lit, Flashlight, LightSet};
+use crate::player::Player;
+use crate::textures::{TextureManager, Transparency};
+
+/// Distancias (perpendiculares, en unidades del mundo; un bloque mide 100) entre las que se
+/// dibujan los sprites.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct SpriteLimits {
+    pub near: f32, // más cerca se saldrían de la pantalla
+    pub far: f32,  // más lejos miden uno o dos píxeles
+}
+
+impl Default for SpriteLimits {
+    fn default() -> Self {
+        SpriteLimits { near: 20.0, far: 4000.0 }
+    }
+}
+
+/// Alto de las paredes en unidades del mundo (un bloque del mapa mide 100 de lado).
+pub const WALL_HEIGHT: f32 = 100.0;
+// La cámara está a media altura de las paredes
+const EYE_HEIGHT: f32 = WALL_HEIGHT / 2.0;
+// Alto por defecto de un sprite: Jack, casi hasta el techo
+const DEFAULT_HEIGHT: f32 = 90.0;
+
+/// Vistas de un sprite direccional.
+pub const VIEW_ANGLES: usize = 8;
+
+/// Una textura por vista: la 0 es de frente (la cámara está hacia donde mira el sprite) y
+/// las siguientes van girando la cámara de 45° en 45° en el sentido en que crecen los ángulos
+/// (la 4 es de espaldas).
+pub type Views = [char; VIEW_ANGLES];
+
+/// A qué altura de la pantalla se pega el sprite.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum VerticalAnchor {
+    Center,      // centrado a la altura de los ojos (a media altura de las paredes)
+    Floor,       // apoyado en el suelo
+    Ceiling,     // colgando del techo
+    Height(f32), // con el borde inferior a esta altura del suelo (partículas en el aire)
+    Flat,        // tendido en el suelo (sombras): `height` es su largo a lo largo de la vista
+}
+
+/// Un billboard en el mundo: siempre mira a la cámara.
+#[derive(Clone, Copy, Debug)]
+pub struct Sprite {
+    pub pos: Vector2,
+    pub key: char,            // textura (clave del manifiesto); la única vista si no hay `views`
+    pub views: Option<Views>, // texturas según desde dónde se le mire
+    pub facing: f32,          // hacia dónde mira (rad), para elegir la vista
+    pub height: f32,          // alto en unidades del mundo (WALL_HEIGHT = una pared)
+    pub width: Option<f32>,   // ancho en unidades del mundo; None = según la proporción de la textura
+    pub anchor: VerticalAnchor,
+    pub alpha: f32,           // opacidad (1 = la de la textura); por debajo de 1 se mezcla con el fondo
+    pub emissive: bool,       // brilla con luz propia: no lo afectan la linterna ni las lámparas
+}
+
+impl Sprite {
+    pub fn new(pos: Vector2, key: char) -> Self {
+        Sprite {
+            pos,
+            key,
+            views: None,
+            facing: 0.0,
+            height: DEFAULT_HEIGHT,
+            width: None,
+            anchor: VerticalAnchor::Floor,
+            alpha: 1.0,
+            emissive: false,
+        }
+    }
+
+    /// El mismo sprite con otro alto (unidades del mundo) y otra ancla.
+    pub fn sized(self, height: f32, anchor: VerticalAnchor) -> Self {
+        Sprite { height, anchor, ..self }
+    }
+
+    /// El mismo sprite con un ancho fijo (si no, sale de la proporción de la textura).
+    pub fn with_width(self, width: f32) -> Self {
+        Sprite { width: Some(width), ..self }
+    }
+
+    /// Sprite con una textura por vista, mirando hacia `facing`.
+    pub fn directional(pos: Vector2, views: Views, facing: f32) -> Self {
+        Sprite { views: Some(views), facing, ..Sprite::new(pos, views[0]) }
+    }
+
+    /// Textura que se ve desde `camera`: la vista más cercana al ángulo entre hacia dónde
+    /// mira el sprite y la dirección en la que está la cámara.
+    pub fn key_seen_from(&self, camera: Vector2) -> char {
+        let Some(views) = self.views else {
+            return self.key;
+        };
+        let to_camera = (camera.y - self.pos.y).atan2(camera.x - self.pos.x);
+        let step = 2.0 * PI / VIEW_ANGLES as f32;
+        let relative = (to_camera - self.facing).rem_euclid(2.0 * PI);
+        views[(relative / step).round() as usize % VIEW_ANGLES]
+    }
+}
+
+/// Dibuja los sprites de lejos a cerca (así los cercanos tapan a los lejanos). Cada columna
+/// se compara con la distancia perpendicular de la pared en `depth_buffer` (ver render_world);
+/// los sprites usan la misma medida para su tamaño, su luz y su oclusión.
+pub fn render_sprites(
+    framebuffer: &mut Framebuffer,
+    player: &Player,
+    sprites: &[Sprite],
+    texture_manager: &TextureManager,
+    depth_buffer: &[f32],
+    flashlight: &Flashlight,
+    lights: &LightSet,
+    limits: SpriteLimits,
+) {
+    let mut order: Vec<(f32, &Sprite)> = sprites
+        .iter()
+        .map(|s| (perpendicular_depth(player, s.pos), s))
+        .filter(|&(depth, _)| depth >= limits.near && depth <= limits.far)
+        .collect();
+    order.sort_by(|a, b| b.0.total_cmp(&a.0));
+    for (depth, sprite) in order {
+        draw_sprite(framebuffer, player, sprite, depth, texture_manager, depth_buffer, flashlight, lights);
+    }
+}
+
+/// Distancia de `pos` a lo largo de la dirección de la vista.
+fn perpendicular_depth(player: &Player, pos: Vector2) -> f32 {
+    (pos.x - player.pos.x) * player.a.cos() + (pos.y - player.pos.y) * player.a.sin()
+}
+
+fn draw_sprite(
+    framebuffer: &mut Framebuffer,
+    player: &Player,
+    sprite: &Sprite,
+    depth: f32,
+    texture_manager: &TextureManager,
+    depth_buffer: &[f32],
+    flashlight: &Flashlight,
+    lights: &LightSet,
+) {
+    let sprite_a = (sprite.pos.y - player.pos.y).atan2(sprite.pos.x - player.pos.x);
+    let mut angle_diff = sprite_a - player.a;
+    while angle_diff > PI {
+        angle_diff -= 2.0 * PI;
+    }
+    while angle_diff < -PI {
+        angle_diff += 2.0 * PI;
+    }
+
+    let screen_height = framebuffer.height as f32;
+    let screen_width = framebuffer.width as f32;
+    let hh = screen_height / 2.0;
+    let key = sprite.key_seen_from(player.pos);
+
+    // misma proyección que las paredes (ver render_world): media pared mide 35 * hh / d px
+    let px_per_unit = 35.0 * hh / (EYE_HEIGHT * depth);
+    // solo la parte visible de la textura ocupa el alto del sprite
+    let (vx, vy, vw, vh) = texture_manager.visible_rect(key);
+    let (tex_x, tex_y, tex_w, tex_h) = (vx as f32, vy as f32, vw as f32, vh as f32);
+    let sprite_w = match sprite.width {
+        Some(width) => width * px_per_unit,
+        None => sprite.height * px_per_unit * tex_w / tex_h,
+    };
+    // fila de la pantalla en la que se ve el suelo a `dist` (como el suelo en render_world)
+    let floor_row = |dist: f32| hh + 35.0 * hh / dist.max(1.0);
+    let half_length = sprite.height / 2.0;
+    let (top, sprite_h) = if sprite.anchor == VerticalAnchor::Flat {
+        // del borde lejano al cercano, con la perspectiva del suelo
+        let top = floor_row(depth + half_length);
+        (top, floor_row(depth - half_length) - top)
+    } else {
+        // altura (en el mundo) del borde inferior del sprite
+        let bottom = match sprite.anchor {
+            VerticalAnchor::Ceiling => WALL_HEIGHT - sprite.height,
+            VerticalAnchor::Center => EYE_HEIGHT - sprite.height / 2.0,
+            VerticalAnchor::Height(z) => z,
+            VerticalAnchor::Floor | VerticalAnchor::Flat => 0.0,
+        };
+        (hh - (bottom + sprite.height - EYE_HEIGHT) * px_per_unit, sprite.height * px_per_unit)
+    };
+    if sprite_h < 1.0 || sprite_w < 1.0 {
+        return;
+    }
+    // fila de la textura para la fila `y` de la pantalla
+    let texture_row = |y: usize| -> f32 {
+        if sprite.anchor == VerticalAnchor::Flat {
+            let dist = 35.0 * hh / (y as f32 + 0.5 - hh).max(0.5);
+            (depth + half_length - dist) / sprite.height * tex_h
+        } else {
+            (y as f32 - top) / sprite_h * tex_h
+        }
+    };
+    let screen_x = ((angle_diff / player.fov) + 0.5) * screen_width;
+    let left = screen_x - sprite_w / 2.0;
+
+    let ang_per_px = player.fov / screen_width;
+    let lamp = lights.light_at(sprite.pos.x, sprite.pos.y);
+    let start_x = left.max(0.0) as usize;
+    let start_y = top.max(0.0) as usize;
+    let end_x = (left + sprite_w).clamp(0.0, screen_width) as usize;
+    let end_y = (top + sprite_h).clamp(0.0, screen_height) as usize;
+
+    let sprite_tpp = tex_h / sprite_h;
+    // un sprite que se desvanece siempre se mezcla, sea cual sea el modo de su textura
+    let transparency = if sprite.alpha < 1.0 { Transparency::AlphaBlend } else { texture_manager.transparency(key) };
+    // en modo retro los sprites también se ven con los colores de la paleta
+    let retro = texture_manager.palette().filter(|p| p.is_retro());
+
+    for x in start_x..end_x {
+        // Ocultación por columna: si el sprite está detrás de la pared en esta columna, se salta
+        if depth >= depth_buffer.get(x).copied().unwrap_or(f32::INFINITY) {
+            continue;
+        }
+
+        let tx_f = ((x as f32 - left) / sprite_w) * tex_w;
+        let tx_c = tex_x + tx_f.max(0.0).min(tex_w - 1.0);
+        for y in start_y..end_y {
+            let ty_f = texture_row(y);
+            let ty_c = tex_y + ty_f.max(0.0).min(tex_h - 1.0);
+
+            let mut color = texture_manager.sample(key, tx_c, ty_c, sprite_tpp);
+            color.a = (color.a as f32 * sprite.alpha.clamp(0.0, 1.0)) as u8;
+
+            if color.a == 0 {
+                continue;
+            }
+
+            let mut lit = if sprite.emissive {
+                color
+            } else {
+                let h_off = (x as f32 - screen_width / 2.0) * ang_per_px;
+                let v_off = (y as f32 - hh) * ang_per_px;
+                let light = flashlight.light_at_screen(h_off, v_off, depth);
+                shade_lit(color, light, lamp)
+            };
+            if let Some(palette) = retro {
+                lit = palette.quantize(lit);
+            }
+            match transparency.resolve(lit) {
+                Some(lit) if lit.a == 255 => framebuffer.put_pixel(x as i32, y as i32, lit),
+                Some(lit) => framebuffer.blend_pixel(x as i32, y as i32, lit),
+                None => {}
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const VIEWS: Views = ['0', '1', '2', '3', '4', '5', '6', '7'];
+
+    /// Vista de un sprite en (100, 100) que mira hacia `facing`, con la cámara en la
+    /// dirección `camera_deg` (grados) vista desde el sprite.
+    fn seen(facing: f32, camera_deg: f32) -> char {
+        let sprite = Sprite::directional(Vector2::new(100.0, 100.0), VIEWS, facing);
+        let a = camera_deg.to_radians();
+        sprite.key_seen_from(Vector2::new(100.0 + 300.0 * a.cos(), 100.0 + 300.0 * a.sin()))
+    }
+
+    #[test]
+    fn key_seen_from_front_back_and_sides() {
+        // mirando a +x: la cámara en +x lo ve de frente y en -x de espaldas
+        assert_eq!(seen(0.0, 0.0), '0');
+        assert_eq!(seen(0.0, 180.0), '4');
+        assert_eq!(seen(0.0, 45.0), '1');
+        assert_eq!(seen(0.0, 90.0), '2');
+        assert_eq!(seen(0.0, -90.0), '6');
+        // se redondea a la vista más cercana
+        assert_eq!(seen(0.0, 20.0), '0');
+        assert_eq!(seen(0.0, 25.0), '1');
+        // lo mismo girando el sprite junto con la cámara
+        assert_eq!(seen(PI / 2.0, 90.0), '0');
+        assert_eq!(seen(PI / 2.0, -90.0), '4');
+        // sin vistas, siempre la misma textura
+        assert_eq!(Sprite::new(Vector2::new(0.0, 0.0), 'p').key_seen_from(Vector2::new(-50.0, 10.0)), 'p');
+    }
+
+    #[test]
+    fn key_seen_from_wraps_around() {
+        // justo antes de dar la vuelta se redondea a la 8, que es otra vez la de frente
+        assert_eq!(seen(0.0, -10.0), '0');
+        assert_eq!(seen(0.0, 350.0), '0');
+        assert_eq!(seen(0.0, -30.0), '7');
+        // ángulos negativos o de varias vueltas dan lo mismo
+        assert_eq!(seen(-PI, 180.0), '0');
+        assert_eq!(seen(-PI, 0.0), '4');
+        assert_eq!(seen(6.0 * PI + PI / 2.0, 90.0), '0');
+        assert_eq!(seen(-4.0 * PI, 135.0), '3');
+    }
+
+    #[test]
+    fn flat_sprites_lie_on_the_floor() {
+        let textures = TextureManager::new();
+        let player = Player { pos: Vector2::new(0.0, 0.0), a: 0.0, fov: PI / 2.0 };
+        let (w, h) = (200, 200);
+        let background = Color::new(200, 200, 200, 255);
+        let mut framebuffer = Framebuffer::new(w, h, background);
+        let shadow = Sprite::new(Vector2::new(300.0, 0.0), '_').sized(60.0, VerticalAnchor::Flat).with_width(60.0);
+        let depth_buffer = vec![f32::INFINITY; w as usize];
+        let flashlight = Flashlight { ambient: 1.0, ..Flashlight::new() };
+        render_sprites(&mut framebuffer, &player, &[shadow], &textures, &depth_buffer, &flashlight, &LightSet::empty(), SpriteLimits::default());
+
+        let drawn: Vec<(i32, i32)> = (0..w * h)
+            .filter(|&i| framebuffer.pixels()[i as usize] != background)
+            .map(|i| (i % w, i / w))
+            .collect();
+        assert!(!drawn.is_empty());
+        // entre las filas del suelo a 270 y a 330 de distancia, y más ancha que alta
+        let hh = h as f32 / 2.0;
+        let (far, near) = (hh + 35.0 * hh / 330.0, hh + 35.0 * hh / 270.0);
+        let rows = drawn.iter().map(|p| p.1);
+        let cols = drawn.iter().map(|p| p.0);
+        let (top, bottom) = (rows.clone().min().unwrap(), rows.max().unwrap());
+        let (left, right) = (cols.clone().min().unwrap(), cols.max().unwrap());
+        assert!(top as f32 >= far.floor() && bottom as f32 <= near.ceil(), "filas {}..{}", top, bottom);
+        assert!(right - left > 2 * (bottom - top), "{}x{}", right - left, bottom - top);
+    }
+}
@@ -0,0 +1,254 @@
+use raylib::prelude::*;
+use std::fmt::Write as _;
+use std::io::{self, Read, Write};
+use std::process::{Command, Stdio};
+use std::sync::mpsc::{self, Receiver};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use crate::framebuffer::Framebuffer;
+use crate::game::{Event, Game};
+use crate::hud::{Hud, HudState, Widget};
+use crate::light::Flashlight;
+use crate::player::move_player;
+use crate::present::Presenter;
+use crate::sprite::SpriteLimits;
+use crate::textures::TextureManager;
+
+// La terminal no aguanta 60 fps por SSH; el juego se mueve a pasos de 60 fps, así que
+// los enemigos avanzan FRAME_STEPS pasos por cada frame dibujado (ver `Game::update`).
+const TERMINAL_FPS: f32 = 20.0;
+const FRAME_STEPS: f32 = 60.0 / TERMINAL_FPS;
+
+/// Presentador de terminal: cada celda de texto muestra dos píxeles con el medio bloque '▀'
+/// (color de letra = píxel de arriba, color de fondo = píxel de abajo) en color verdadero ANSI.
+/// El framebuffer debe medir columnas x (filas * 2).
+pub struct TerminalPresenter {
+    out: String,
+}
+
+impl TerminalPresenter {
+    pub fn new() -> Self {
+        TerminalPresenter { out: String::new() }
+    }
+}
+
+impl Presenter for TerminalPresenter {
+    fn present(&mut self, frame: &Framebuffer) {
+        let (w, h) = (frame.width as usize, frame.height as usize);
+        let pixels = frame.pixels();
+        let out = &mut self.out;
+        out.clear();
+        out.push_str("\x1b[H");
+
+        for y in (0..h).step_by(2) {
+            // solo se cambia de color cuando cambia el par (arriba, abajo)
+            let mut last: Option<(Color, Color)> = None;
+            for x in 0..w {
+                let top = pixels[y * w + x];
+                let bottom = if y + 1 < h { pixels[(y + 1) * w + x] } else { Color::BLACK };
+                if last != Some((top, bottom)) {
+                    let _ = write!(
+                        out,
+                        "\x1b[38;2;{};{};{}m\x1b[48;2;{};{};{}m",
+                        top.r, top.g, top.b, bottom.r, bottom.g, bottom.b
+                    );
+                    last = Some((top, bottom));
+                }
+                out.push('▀');
+            }
+            // \r\n: en modo crudo el salto de línea no vuelve al inicio
+            out.push_str("\x1b[0m\r\n");
+        }
+
+        let mut stdout = io::stdout().lock();
+        let _ = stdout.write_all(out.as_bytes());
+        let _ = stdout.flush();
+    }
+}
+
+/// Pone la terminal en modo crudo (sin eco ni espera de Enter) y la deja como estaba al soltarse.
+struct RawMode {
+    saved: Option<String>,
+}
+
+impl RawMode {
+    fn enable() -> Self {
+        let saved = stty(&["-g"]).map(|s| s.trim().to_string());
+        let _ = stty(&["raw", "-echo"]);
+        print!("\x1b[?25l\x1b[2J");
+        RawMode { saved }
+    }
+}
+
+impl Drop for RawMode {
+    fn drop(&mut self) {
+        if let Some(saved) = &self.saved {
+            let _ = stty(&[saved]);
+        }
+        print!("\x1b[0m\x1b[2J\x1b[H\x1b[?25h");
+        let _ = io::stdout().flush();
+    }
+}
+
+fn stty(args: &[&str]) -> Option<String> {
+    let output = Command::new("stty").args(args).stdin(Stdio::inherit()).output().ok()?;
+    output.status.success().then(|| String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
+/// Tamaño de la terminal (columnas, filas); 120x40 si no se puede averiguar.
+fn terminal_size() -> (usize, usize) {
+    stty(&["size"])
+        .and_then(|s| {
+            let mut it = s.split_whitespace().filter_map(|n| n.parse::<usize>().ok());
+            let rows = it.next()?;
+            let cols = it.next()?;
+            Some((cols, rows))
+        })
+        .filter(|&(cols, rows)| cols > 0 && rows > 1)
+        .unwrap_or((120, 40))
+}
+
+/// Teclas que entiende el modo terminal.
+enum Key {
+    Forward,
+    Back,
+    Left,
+    Right,
+    Use,
+    Quit,
+}
+
+/// Lee stdin en otro hilo (la lectura bloquea) y manda los bytes por el canal.
+fn spawn_input() -> Receiver<u8> {
+    let (tx, rx) = mpsc::channel();
+    thread::spawn(move || {
+        let mut stdin = io::stdin();
+        let mut byte = [0u8; 1];
+        while let Ok(1) = stdin.read(&mut byte) {
+            if tx.send(byte[0]).is_err() {
+                break;
+            }
+        }
+    });
+    rx
+}
+
+/// Traduce los bytes pendientes a teclas: flechas (ESC [ A..D), wasd, 'e', 'q' o Ctrl-C.
+fn drain_keys(input: &Receiver<u8>) -> Vec<Key> {
+    let bytes: Vec<u8> = input.try_iter().collect();
+    let mut keys = Vec::new();
+    let mut i = 0;
+    while i < bytes.len() {
+        match bytes[i] {
+            0x1b if bytes.get(i + 1) == Some(&b'[') && i + 2 < bytes.len() => {
+                match bytes[i + 2] {
+                    b'A' => keys.push(Key::Forward),
+                    b'B' => keys.push(Key::Back),
+                    b'C' => keys.push(Key::Right),
+                    b'D' => keys.push(Key::Left),
+                    _ => {}
+                }
+                i += 2;
+            }
+            // solo minúsculas: si una secuencia de flecha llega partida, su 'A'..'D' no cuenta
+            b'w' => keys.push(Key::Forward),
+            b's' => keys.push(Key::Back),
+            b'a' => keys.push(Key::Left),
+            b'd' => keys.push(Key::Right),
+            b'e' => keys.push(Key::Use),
+            b'q' | 3 => keys.push(Key::Quit),
+            _ => {}
+        }
+        i += 1;
+    }
+    keys
+}
+
+/// Juega en la terminal (para SSH): flechas/WASD para moverse, 'e' en la salida, 'q' para salir.
+/// Las reglas son las de `Game`, igual que con ventana; aquí solo van la entrada y el dibujo.
+/// Devuelve el código de salida del proceso.
+pub fn run(levels: &[i32], start_level: i32) -> i32 {
+    let block_size = crate::game::BLOCK_SIZE;
+    let mut game = Game::new(levels, start_level);
+
+    let texture_cache = TextureManager::new();
+    let (cols, rows) = terminal_size();
+    // la última fila se deja libre para que la terminal no haga scroll
+    let (width, height) = (cols as i32, (rows as i32 - 1) * 2);
+    let mut framebuffer = Framebuffer::new(width, height, Color::new(50, 50, 100, 255));
+    let mut depth_buffer = vec![f32::INFINITY; width as usize];
+    let mut presenter = TerminalPresenter::new();
+    // a esta resolución el minimapa taparía media pantalla
+    let hud = Hud::with(&[Widget::Floor, Widget::Health]);
+    let render_threads = thread::available_parallelism().map_or(1, |n| n.get()).min(8);
+    let mut flashlight = Flashlight::new();
+    let mut message: Option<&str> = None;
+
+    let raw = RawMode::enable();
+    let input = spawn_input();
+    let started = Instant::now();
+    let frame_time = Duration::from_secs_f32(1.0 / TERMINAL_FPS);
+    let dt = 1.0 / TERMINAL_FPS;
+
+    'game: loop {
+        let frame_start = Instant::now();
+
+        for key in drain_keys(&input) {
+            match key {
+                Key::Forward => move_player(&mut game.player, &game.maze, 1.0, 0.0),
+                Key::Back => move_player(&mut game.player, &game.maze, -1.0, 0.0),
+                Key::Left => move_player(&mut game.player, &game.maze, 0.0, -1.0),
+                Key::Right => move_player(&mut game.player, &game.maze, 0.0, 1.0),
+                Key::Quit => break 'game,
+                Key::Use => {
+                    if game.use_exit() == Some(Event::Escaped) {
+                        message = Some("¡Escapaste!");
+                        break 'game;
+                    }
+                }
+            }
+        }
+
+        if game.update(dt, FRAME_STEPS) == Some(Event::Caught) {
+            message = Some("Jack te atrapó");
+            break 'game;
+        }
+
+        let time = started.elapsed().as_secs_f32();
+        flashlight.follow_enemies(&game.player, &game.enemies);
+        game.lights.update(time, dt, &game.player, &game.enemies, &game.maze, block_size);
+
+        framebuffer.clear();
+        depth_buffer.fill(f32::INFINITY);
+        let current_level = game.current_level();
+        crate::render_world(&mut framebuffer, &game.player, &game.maze, &texture_cache, &mut depth_buffer, current_level as usize, &flashlight, &game.lights, &game.decals, time, render_threads);
+        let sprites = crate::world_sprites(&game.player, &game.maze, &game.enemies, &game.lights, current_level as usize);
+        crate::sprite::render_sprites(&mut framebuffer, &game.player, &sprites, &texture_cache, &depth_buffer, &flashlight, &game.lights, SpriteLimits::default());
+        let hud_state = HudState {
+            fps: TERMINAL_FPS as i32,
+            level: current_level,
+            lives: game.lives,
+            max_lives: game.max_lives,
+            maze: &game.maze,
+            player: &game.player,
+            enemies: &game.enemies,
+            block_size,
+            view_3d: true,
+            recording: false,
+            message: None,
+        };
+        hud.draw(&mut framebuffer, &hud_state);
+        framebuffer.swap_buffers(&mut presenter);
+
+        if let Some(rest) = frame_time.checked_sub(frame_start.elapsed()) {
+            thread::sleep(rest);
+        }
+    }
+
+    drop(raw);
+    if let Some(message) = message {
+        println!("{}", message);
+    }
+    0
+}
@@ -1,92 +1,873 @@
 use raylib::prelude::*;
 use std::collections::HashMap;
+use std::fs;
 use std::slice;
+use std::time::SystemTime;
+
+use crate::palette::Palette;
+use crate::procgen::{self, Pattern, Recipe};
+
+/// Un nivel de la cadena de mipmaps: colores ya decodificados y su tamaño.
+struct MipLevel {
+    colors: Vec<Color>,
+    indices: Vec<u8>, // los mismos texeles en la paleta compartida (vacío si no hay paleta)
+    w: i32,
+    h: i32,
+}
+
+/// Una textura: uno o más cuadros (cada uno con su cadena de mipmaps).
+struct TextureFrames {
+    frames: Vec<Vec<MipLevel>>,
+    fps: f32, // cuadros por segundo (0 = estática)
+    transparency: Transparency,
+    visible: (i32, i32, i32, i32), // (x, y, ancho, alto) de la parte no transparente, en todos los cuadros
+}
+
+/// Color clave por defecto: los texeles de este color se vuelven transparentes
+/// en las texturas con `alpha=key`.
+pub const TRANSPARENT_COLOR: Color = Color::new(152, 0, 136, 255);
+
+/// Alpha por debajo del cual un texel no se dibuja (en `AlphaTest` y `ColorKey`).
+pub const ALPHA_CUTOFF: u8 = 128;
+
+/// Cómo se dibujan los texeles transparentes de una textura (sprites).
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Transparency {
+    /// Los texeles de este color se descartan (se vuelven alpha 0 al cargar).
+    ColorKey(Color),
+    /// Todo o nada: se dibuja opaco si alpha >= ALPHA_CUTOFF. Es el modo por defecto.
+    AlphaTest,
+    /// Se mezcla con lo que hay detrás según su alpha (fantasmas, niebla, vidrio).
+    AlphaBlend,
+}
+
+impl Transparency {
+    /// Color con el que se pinta un texel (ya iluminado) con este modo: None si no se dibuja,
+    /// alpha 255 si tapa lo de atrás, o el alpha del texel si hay que mezclarlo.
+    pub fn resolve(self, color: Color) -> Option<Color> {
+        match self {
+            Transparency::AlphaBlend if color.a > 0 => Some(color),
+            Transparency::AlphaBlend => None,
+            _ if color.a >= ALPHA_CUTOFF => Some(Color { a: 255, ..color }),
+            _ => None,
+        }
+    }
+}
+
+/// De dónde salen los cuadros de una textura (según el manifiesto).
+#[derive(Clone)]
+enum FrameSource {
+    Single(String),        // una imagen, un cuadro
+    Sequence(Vec<String>), // un archivo por cuadro
+    Strip(String, usize),  // tira horizontal con N cuadros del mismo ancho
+    Pulse(String, usize),  // N cuadros derivados de una imagen, con el brillo latiendo
+    Generated(Recipe),     // hecha en código (ver procgen), sin archivo
+}
+
+/// Una línea del manifiesto: clave, cuadros y velocidad de animación.
+#[derive(Clone)]
+struct AssetEntry {
+    key: char,
+    source: FrameSource,
+    fps: f32,
+    transparency: Transparency,
+    fallback: Option<Recipe>, // se genera esta si el archivo no carga (en vez del tablero)
+}
 
 pub struct TextureManager {
-    // Guardamos los colores ya decodificados por Raylib, más width/height
-    images: HashMap<char, (Vec<Color>, i32, i32)>,
-    textures: HashMap<char, Texture2D>, // GPU textures para dibujar
+    // Por textura, sus cuadros; cada cuadro es una cadena de mipmaps (nivel 0 = imagen original)
+    images: HashMap<char, TextureFrames>,
+    use_mipmaps: bool,
+    bilinear: bool,
+    manifest_path: String,
+    entries: Vec<AssetEntry>,
+    // texturas generadas registradas desde el código; sobreviven a recargar el manifiesto
+    registered: Vec<AssetEntry>,
+    // reporte de carga: resultado por clave y errores del propio manifiesto
+    status: HashMap<char, Result<String, String>>,
+    manifest_errors: Vec<String>,
+    // última modificación vista de cada archivo (manifiesto incluido), para recargar en caliente
+    watched: HashMap<String, Option<SystemTime>>,
+    placeholder: TextureFrames,
+    // paleta compartida para el sombreado por tablas (None = colores verdaderos)
+    palette: Option<Palette>,
 }
 
+// Manifiesto por defecto; si no se puede leer se usa la copia incluida en el ejecutable
+pub const MANIFEST_PATH: &str = "assets/textures.txt";
+const BUILTIN_MANIFEST: &str = include_str!("../assets/textures.txt");
+
 impl TextureManager {
-    pub fn new(rl: &mut RaylibHandle, thread: &RaylibThread) -> Self {
-        let mut images = HashMap::new();
-        let mut textures = HashMap::new();
-
-
-        let texture_files = vec![
-            ('+', "assets/pared.png"),
-            ('|', "assets/pared.png"),
-            ('g', "assets/wallPaint.png"),
-            ('f', "assets/alfombraCIT.png"),
-            ('e', "assets/jack1.png"),
-            ('E',"assets/jack2.png"),
-            ('c',"assets/techo.png"),
-            ('-',"assets/uvgLogo.png"),
-            ('3',"assets/L_3.png"),
-            ('4',"assets/L_4.png"),
-            ('5',"assets/L_5.png"),
-            ('6',"assets/L_6.png"),
-            ('7',"assets/L_7.png"),
-            ('k',"assets/sky.png"),
-            ('p',"assets/estatua.png")
-        ];
-
-        for (ch, path) in texture_files {
-            match Image::load_image(path) {
-                Ok(mut image) => {
-                    println!("Cargada imagen para '{}': {} ({}x{})", ch, path, image.width, image.height);
-
-                    // Load texture GPU (lo tuyo)
-                    let texture = rl.load_texture(thread, path).expect(&format!("Failed to load texture {}", path));
-
-                    textures.insert(ch, texture);
-
-          
-                    let w = image.width;
-                    let h = image.height;
-                    unsafe {
-                    let colors_ptr: *mut raylib::ffi::Color =
-                        raylib::ffi::LoadImageColors(*image.as_ref());
-
-                    let len = (w as usize) * (h as usize);
-                    let slice = std::slice::from_raw_parts(colors_ptr, len);
-                    let colors_vec: Vec<Color> = slice
-                        .iter()
-                        .map(|c| Color { r: c.r, g: c.g, b: c.b, a: c.a })
-                        .collect();
-                    raylib::ffi::UnloadImageColors(colors_ptr);
+    /// Carga las texturas del manifiesto por defecto, solo en CPU (las que muestrea el renderer).
+    /// No necesita ventana ni GPU: a la GPU solo se sube el framebuffer ya dibujado.
+    pub fn new() -> Self {
+        Self::from_manifest(MANIFEST_PATH)
+    }
 
-                    images.insert(ch, (colors_vec, w, h));
-                }
+    /// Carga las texturas que declara el manifiesto `path` e imprime el reporte de carga.
+    pub fn from_manifest(path: &str) -> Self {
+        let mut manager = TextureManager {
+            images: HashMap::new(),
+            use_mipmaps: true,
+            bilinear: false,
+            manifest_path: path.to_string(),
+            entries: Vec::new(),
+            registered: Vec::new(),
+            status: HashMap::new(),
+            manifest_errors: Vec::new(),
+            watched: HashMap::new(),
+            placeholder: placeholder_frames(),
+            palette: None,
+        };
+        manager.load_all();
+        manager.print_report();
+        manager
+    }
 
+    /// (Re)lee el manifiesto y carga todas sus texturas.
+    fn load_all(&mut self) {
+        self.manifest_errors.clear();
+        let text = match fs::read_to_string(&self.manifest_path) {
+            Ok(text) => text,
+            Err(e) => {
+                self.manifest_errors.push(format!(
+                    "{}: {} (se usa el manifiesto incluido en el juego)",
+                    self.manifest_path, e
+                ));
+                BUILTIN_MANIFEST.to_string()
+            }
+        };
+        let (entries, errors) = parse_manifest(&self.manifest_path, &text);
+        self.manifest_errors.extend(errors);
 
-                }
-                Err(e) => {
-                    eprintln!("Error al cargar imagen para '{}': {}. Error: {}", ch, path, e);
-                }
+        self.images.clear();
+        self.status.clear();
+        self.watched.clear();
+        self.watched.insert(self.manifest_path.clone(), modified(&self.manifest_path));
+        self.entries = entries;
+        for entry in &self.registered {
+            self.entries.retain(|e| e.key != entry.key);
+            self.entries.push(entry.clone());
+        }
+        for index in 0..self.entries.len() {
+            self.load_entry(index);
+        }
+    }
+
+    /// Registra una textura generada con `recipe` bajo `key` (reemplaza la que hubiera).
+    pub fn register(&mut self, key: char, recipe: Recipe, transparency: Transparency) {
+        let entry = AssetEntry { key, source: FrameSource::Generated(recipe), fps: 0.0, transparency, fallback: None };
+        self.registered.retain(|e| e.key != key);
+        self.registered.push(entry.clone());
+        self.entries.retain(|e| e.key != key);
+        self.entries.push(entry);
+        self.load_entry(self.entries.len() - 1);
+    }
+
+    /// Carga (o recarga) una textura del manifiesto; si falla queda su textura de respaldo
+    /// generada o, si no tiene, el tablero de reemplazo.
+    fn load_entry(&mut self, index: usize) {
+        let entry = &self.entries[index];
+        for path in entry.source.paths() {
+            self.watched.insert(path.to_string(), modified(path));
+        }
+        let (key, fps, transparency) = (entry.key, entry.fps, entry.transparency);
+        match load_frames(&entry.source) {
+            Ok(frames) => {
+                let (w, h) = (frames[0].1, frames[0].2);
+                let count = frames.len();
+                let summary = if count > 1 {
+                    format!("{} ({}x{}, {} cuadros a {} fps)", entry.source.describe(), w, h, count, fps)
+                } else {
+                    format!("{} ({}x{})", entry.source.describe(), w, h)
+                };
+                self.images.insert(key, build_frames(frames, fps, transparency));
+                self.status.insert(key, Ok(summary));
             }
+            Err(e) => {
+                let frames = match entry.fallback {
+                    Some(recipe) => build_frames(vec![recipe.generate()], 0.0, transparency),
+                    None => placeholder_frames(),
+                };
+                self.images.insert(key, frames);
+                self.status.insert(key, Err(e));
+            }
+        }
+        if let (Some(palette), Some(tex)) = (&self.palette, self.images.get_mut(&key)) {
+            index_frames(tex, palette);
         }
+    }
 
-        TextureManager { images, textures }
+    /// Revisa si cambió el manifiesto o alguna imagen y recarga lo necesario.
+    /// Devuelve las claves recargadas (vacío si nada cambió).
+    pub fn reload_changed(&mut self) -> Vec<char> {
+        let changed = |watched: &HashMap<String, Option<SystemTime>>, path: &str| {
+            watched.get(path).map_or(true, |&seen| modified(path) != seen)
+        };
+
+        if changed(&self.watched, &self.manifest_path) {
+            self.load_all();
+            self.print_report();
+            return self.entries.iter().map(|e| e.key).collect();
+        }
+
+        let stale: Vec<usize> = (0..self.entries.len())
+            .filter(|&i| self.entries[i].source.paths().iter().any(|p| changed(&self.watched, p)))
+            .collect();
+        for &index in &stale {
+            self.load_entry(index);
+            let key = self.entries[index].key;
+            match &self.status[&key] {
+                Ok(summary) => println!("Recargada textura '{}': {}", key, summary),
+                Err(e) => eprintln!("Error al recargar textura '{}': {}", key, e),
+            }
+        }
+        stale.iter().map(|&i| self.entries[i].key).collect()
     }
 
+    /// Problemas de la última carga (errores del manifiesto y texturas que no se pudieron cargar).
+    pub fn problems(&self) -> Vec<String> {
+        let mut problems = self.manifest_errors.clone();
+        for entry in &self.entries {
+            if let Some(Err(e)) = self.status.get(&entry.key) {
+                problems.push(format!("'{}': {}", entry.key, e));
+            }
+        }
+        problems
+    }
+
+    /// Imprime el reporte de carga: una línea por textura y los problemas al final.
+    pub fn print_report(&self) {
+        println!("Texturas ({}):", self.manifest_path);
+        for entry in &self.entries {
+            match self.status.get(&entry.key) {
+                Some(Ok(summary)) => println!("  '{}' ok     {}", entry.key, summary),
+                Some(Err(_)) => match &entry.fallback {
+                    Some(recipe) => println!("  '{}' FALTA  {} (se usa {})", entry.key, entry.source.describe(), recipe.describe()),
+                    None => println!("  '{}' FALTA  {} (se ve como tablero)", entry.key, entry.source.describe()),
+                },
+                None => {}
+            }
+        }
+        let problems = self.problems();
+        if !problems.is_empty() {
+            eprintln!("{} problema(s) al cargar texturas:", problems.len());
+            for problem in problems {
+                eprintln!("  {}", problem);
+            }
+        }
+    }
+
+    /// Configura el muestreo de `sample` (desde los ajustes del juego).
+    pub fn set_filtering(&mut self, use_mipmaps: bool, bilinear: bool) {
+        self.use_mipmaps = use_mipmaps;
+        self.bilinear = bilinear;
+    }
+
+    /// Lectura directa (vecino más cercano) del nivel 0. Claves desconocidas: tablero de reemplazo.
     pub fn get_pixel_color(&self, ch: char, tx: u32, ty: u32) -> Color {
-        if let Some((colors, w, h)) = self.images.get(&ch) {
-            let max_x = ( (*w as u32).saturating_sub(1) ) as usize;
-            let max_y = ( (*h as u32).saturating_sub(1) ) as usize;
-            let x = (tx as usize).min(max_x);
-            let y = (ty as usize).min(max_y);
-            let idx = y * (*w as usize) + x;
-            // colors[idx] es Color (raylib::prelude::Color) y es Copy
-            colors[idx]
+        self.frames_for(ch).frames[0][0].texel(tx as usize, ty as usize)
+    }
+
+    /// Cuadros de la textura, o el tablero de reemplazo si la clave no está en el manifiesto.
+    fn frames_for(&self, ch: char) -> &TextureFrames {
+        self.images.get(&ch).unwrap_or(&self.placeholder)
+    }
+
+    /// Pasa todas las texturas a una paleta compartida de 256 colores (armada con sus propios
+    /// colores) para sombrear con tablas. Con `retro` el resultado también queda en la paleta.
+    pub fn enable_palette(&mut self, retro: bool) {
+        // muestra de texeles de todas las texturas (a lo más ~64k de cada una)
+        let mut samples = Vec::new();
+        for tex in self.images.values() {
+            for frame in &tex.frames {
+                let colors = &frame[0].colors;
+                let stride = (colors.len() / 65536).max(1);
+                samples.extend(colors.iter().step_by(stride).filter(|c| c.a >= ALPHA_CUTOFF));
+            }
+        }
+        let palette = Palette::from_samples(&samples, retro);
+        for tex in self.images.values_mut() {
+            index_frames(tex, &palette);
+        }
+        index_frames(&mut self.placeholder, &palette);
+        println!("Paleta de {} colores{}", palette.len(), if retro { " (modo retro)" } else { "" });
+        self.palette = Some(palette);
+    }
+
+    /// La paleta compartida, si se activó con `enable_palette`.
+    pub fn palette(&self) -> Option<&Palette> {
+        self.palette.as_ref()
+    }
+
+    /// Como `sample_at`, pero devuelve el índice en la paleta y el alpha del texel (vecino más
+    /// cercano: mezclar índices no tiene sentido). Solo con paleta activa; si no, (0, 0).
+    pub fn sample_index_at(&self, ch: char, time: f32, tx: f32, ty: f32, texels_per_px: f32) -> (u8, u8) {
+        let tex = self.frames_for(ch);
+        let levels = &tex.frames[self.frame_at(ch, time).min(tex.frames.len() - 1)];
+        let level = if self.use_mipmaps && texels_per_px > 1.0 {
+            (texels_per_px.log2().floor() as usize).min(levels.len() - 1)
+        } else {
+            0
+        };
+        let scale = (1u32 << level) as f32;
+        let mip = &levels[level];
+        let offset = mip.offset((tx.max(0.0) / scale) as usize, (ty.max(0.0) / scale) as usize);
+        match mip.indices.get(offset) {
+            Some(&index) => (index, mip.colors[offset].a),
+            None => (0, 0),
+        }
+    }
+
+    /// Modo de transparencia de la textura (AlphaTest si la clave no existe).
+    pub fn transparency(&self, ch: char) -> Transparency {
+        self.frames_for(ch).transparency
+    }
+
+    /// Parte visible (x, y, ancho, alto) del nivel 0, sin los bordes transparentes: es lo que
+    /// ocupa un sprite (así uno apoyado en el suelo no flota por el margen de la imagen).
+    pub fn visible_rect(&self, ch: char) -> (i32, i32, i32, i32) {
+        self.frames_for(ch).visible
+    }
+
+    /// Tamaño (ancho, alto) del nivel 0 de la textura, si existe.
+    pub fn size(&self, ch: char) -> Option<(i32, i32)> {
+        self.images.get(&ch).map(|tex| (tex.frames[0][0].w, tex.frames[0][0].h))
+    }
+
+    /// Muestrea la textura en (tx, ty), en texeles del nivel 0.
+    /// `texels_per_px` es cuántos texeles del nivel 0 cubre un píxel de pantalla:
+    /// con mipmaps activos decide el nivel (1 -> nivel 0, 2 -> nivel 1, 4 -> nivel 2...).
+    pub fn sample(&self, ch: char, tx: f32, ty: f32, texels_per_px: f32) -> Color {
+        self.sample_frame(ch, 0, tx, ty, texels_per_px)
+    }
+
+    /// Como `sample`, pero en textura animada toma el cuadro que toca en el instante `time` (s).
+    pub fn sample_at(&self, ch: char, time: f32, tx: f32, ty: f32, texels_per_px: f32) -> Color {
+        self.sample_frame(ch, self.frame_at(ch, time), tx, ty, texels_per_px)
+    }
+
+    /// Cuadro que toca en el instante `time` (0 en texturas estáticas).
+    fn frame_at(&self, ch: char, time: f32) -> usize {
+        match self.images.get(&ch) {
+            Some(tex) if tex.frames.len() > 1 && tex.fps > 0.0 => {
+                (time.max(0.0) * tex.fps) as usize % tex.frames.len()
+            }
+            _ => 0,
+        }
+    }
+
+    fn sample_frame(&self, ch: char, frame: usize, tx: f32, ty: f32, texels_per_px: f32) -> Color {
+        let tex = self.frames_for(ch);
+        let levels = &tex.frames[frame.min(tex.frames.len() - 1)];
+
+        let level = if self.use_mipmaps && texels_per_px > 1.0 {
+            (texels_per_px.log2().floor() as usize).min(levels.len() - 1)
         } else {
-            Color::WHITE
+            0
+        };
+        let scale = (1u32 << level) as f32;
+        let mip = &levels[level];
+        let (u, v) = (tx.max(0.0) / scale, ty.max(0.0) / scale);
+
+        if !self.bilinear {
+            return mip.texel(u as usize, v as usize);
+        }
+
+        // bilineal: mezcla de los 4 texeles vecinos (centros en +0.5)
+        let u = (u - 0.5).max(0.0);
+        let v = (v - 0.5).max(0.0);
+        let (x0, y0) = (u as usize, v as usize);
+        let (fx, fy) = (u.fract(), v.fract());
+        let c00 = mip.texel(x0, y0);
+        let c10 = mip.texel(x0 + 1, y0);
+        let c01 = mip.texel(x0, y0 + 1);
+        let c11 = mip.texel(x0 + 1, y0 + 1);
+        let mix = |a: u8, b: u8, c: u8, d: u8| -> u8 {
+            let top = a as f32 + (b as f32 - a as f32) * fx;
+            let bottom = c as f32 + (d as f32 - c as f32) * fx;
+            (top + (bottom - top) * fy).round() as u8
+        };
+        Color::new(
+            mix(c00.r, c10.r, c01.r, c11.r),
+            mix(c00.g, c10.g, c01.g, c11.g),
+            mix(c00.b, c10.b, c01.b, c11.b),
+            mix(c00.a, c10.a, c01.a, c11.a),
+        )
+    }
+}
+
+
+impl MipLevel {
+    /// Texel en (x, y), recortado al borde de la imagen.
+    fn texel(&self, x: usize, y: usize) -> Color {
+        self.colors[self.offset(x, y)]
+    }
+
+    /// Posición del texel (x, y) en `colors`/`indices`, recortado al borde de la imagen.
+    fn offset(&self, x: usize, y: usize) -> usize {
+        let max_x = (self.w as usize).saturating_sub(1);
+        let max_y = (self.h as usize).saturating_sub(1);
+        y.min(max_y) * (self.w as usize) + x.min(max_x)
+    }
+}
+
+/// Calcula los índices en la paleta de todos los niveles de todos los cuadros.
+fn index_frames(tex: &mut TextureFrames, palette: &Palette) {
+    for level in tex.frames.iter_mut().flatten() {
+        level.indices = level.colors.iter().map(|&c| palette.nearest(c)).collect();
+    }
+}
+
+/// Arma una textura con los cuadros ya decodificados (aplica el color clave y genera los mipmaps).
+fn build_frames(frames: Vec<(Vec<Color>, i32, i32)>, fps: f32, transparency: Transparency) -> TextureFrames {
+    let frames: Vec<Vec<MipLevel>> = frames
+        .into_iter()
+        .map(|(mut colors, w, h)| {
+            // el color clave se vuelve alpha 0 antes de los mipmaps, así no tiñe los bordes
+            if let Transparency::ColorKey(keyed) = transparency {
+                for c in colors.iter_mut().filter(|c| (c.r, c.g, c.b) == (keyed.r, keyed.g, keyed.b)) {
+                    *c = Color::new(0, 0, 0, 0);
+                }
+            }
+            build_mip_chain(colors, w, h)
+        })
+        .collect();
+    let visible = visible_rect(&frames);
+    TextureFrames { frames, fps, transparency, visible }
+}
+
+/// Rectángulo (x, y, ancho, alto) que cubre los texeles con alpha > 0 de todos los cuadros;
+/// la imagen entera si es toda transparente.
+fn visible_rect(frames: &[Vec<MipLevel>]) -> (i32, i32, i32, i32) {
+    let (mut x0, mut y0, mut x1, mut y1) = (i32::MAX, i32::MAX, i32::MIN, i32::MIN);
+    for level in frames.iter().map(|f| &f[0]) {
+        for (i, c) in level.colors.iter().enumerate() {
+            if c.a > 0 {
+                let (x, y) = (i as i32 % level.w, i as i32 / level.w);
+                (x0, y0, x1, y1) = (x0.min(x), y0.min(y), x1.max(x), y1.max(y));
+            }
+        }
+    }
+    if x0 > x1 {
+        let level = &frames[0][0];
+        return (0, 0, level.w, level.h);
+    }
+    (x0, y0, x1 - x0 + 1, y1 - y0 + 1)
+}
+
+/// Genera la cadena de mipmaps promediando bloques de 2x2 hasta llegar a 1x1.
+fn build_mip_chain(colors: Vec<Color>, w: i32, h: i32) -> Vec<MipLevel> {
+    let mut levels = vec![MipLevel { colors, indices: Vec::new(), w, h }];
+
+    loop {
+        let prev = levels.last().unwrap();
+        if prev.w <= 1 && prev.h <= 1 {
+            break;
+        }
+        let nw = (prev.w / 2).max(1);
+        let nh = (prev.h / 2).max(1);
+        let mut colors = Vec::with_capacity((nw * nh) as usize);
+
+        for y in 0..nh as usize {
+            for x in 0..nw as usize {
+                // promedio ponderado por alpha: los texeles transparentes no oscurecen los bordes
+                let (mut r, mut g, mut b, mut a) = (0u32, 0u32, 0u32, 0u32);
+                for (dx, dy) in [(0, 0), (1, 0), (0, 1), (1, 1)] {
+                    let c = prev.texel(x * 2 + dx, y * 2 + dy);
+                    r += c.r as u32 * c.a as u32;
+                    g += c.g as u32 * c.a as u32;
+                    b += c.b as u32 * c.a as u32;
+                    a += c.a as u32;
+                }
+                let color = if a == 0 {
+                    Color::new(0, 0, 0, 0)
+                } else {
+                    Color::new((r / a) as u8, (g / a) as u8, (b / a) as u8, (a / 4) as u8)
+                };
+                colors.push(color);
+            }
         }
+        levels.push(MipLevel { colors, indices: Vec::new(), w: nw, h: nh });
     }
 
-    pub fn get_texture(&self, ch: char) -> Option<&Texture2D> {
-        self.textures.get(&ch)
+    levels
+}
+
+/// Decodifica una imagen (PNG, JPG...; el formato se detecta por el contenido) a colores RGBA
+/// en CPU. No necesita ventana ni GPU.
+pub fn load_colors(path: &str) -> Result<(Vec<Color>, i32, i32), String> {
+    let image = Image::load_image(path).map_err(|e| format!("{}: {}", path, e))?;
+    let (w, h) = (image.width(), image.height());
+    if w <= 0 || h <= 0 {
+        return Err(format!("{}: imagen vacía", path));
+    }
+    let colors: Vec<Color> = image.get_image_data().iter().copied().collect();
+    if colors.len() != (w * h) as usize {
+        return Err(format!("{}: se esperaban {}x{} píxeles y hay {}", path, w, h, colors.len()));
     }
+    Ok((colors, w, h))
 }
 
+/// Carga los cuadros (colores, ancho, alto) de una textura animada.
+fn load_frames(source: &FrameSource) -> Result<Vec<(Vec<Color>, i32, i32)>, String> {
+    match source {
+        FrameSource::Single(path) => Ok(vec![load_colors(path)?]),
+        FrameSource::Generated(recipe) => Ok(vec![recipe.generate()]),
+        FrameSource::Sequence(paths) => paths.iter().map(|path| load_colors(path)).collect(),
+        FrameSource::Strip(path, count) => {
+            let (colors, w, h) = load_colors(path)?;
+            let count = (*count).max(1);
+            let fw = w as usize / count;
+            if fw == 0 {
+                return Err(format!("{}: la tira mide {} px, no caben {} cuadros", path, w, count));
+            }
+            Ok((0..count)
+                .map(|f| {
+                    let mut frame = Vec::with_capacity(fw * h as usize);
+                    for y in 0..h as usize {
+                        let row = y * w as usize + f * fw;
+                        frame.extend_from_slice(&colors[row..row + fw]);
+                    }
+                    (frame, fw as i32, h)
+                })
+                .collect())
+        }
+        FrameSource::Pulse(path, count) => {
+            let (colors, w, h) = load_colors(path)?;
+            let count = (*count).max(1);
+            Ok((0..count)
+                .map(|f| {
+                    // brillo entre 0.6 y 1.0 siguiendo un coseno a lo largo del ciclo
+                    let phase = f as f32 / count as f32 * std::f32::consts::TAU;
+                    let k = 0.8 - 0.2 * phase.cos();
+                    let frame = colors
+                        .iter()
+                        .map(|c| Color::new(
+                            (c.r as f32 * k) as u8,
+                            (c.g as f32 * k) as u8,
+                            (c.b as f32 * k) as u8,
+                            c.a,
+                        ))
+                        .collect();
+                    (frame, w, h)
+                })
+                .collect())
+        }
+    }
+}
+
+impl FrameSource {
+    /// Archivos de los que depende (para vigilar cambios).
+    fn paths(&self) -> Vec<&str> {
+        match self {
+            FrameSource::Single(path) | FrameSource::Strip(path, _) | FrameSource::Pulse(path, _) => vec![path],
+            FrameSource::Sequence(paths) => paths.iter().map(|p| p.as_str()).collect(),
+            FrameSource::Generated(_) => Vec::new(),
+        }
+    }
+
+    fn describe(&self) -> String {
+        match self {
+            FrameSource::Single(path) => path.clone(),
+            FrameSource::Sequence(paths) => paths.join(", "),
+            FrameSource::Strip(path, n) => format!("{} (tira de {})", path, n),
+            FrameSource::Pulse(path, n) => format!("{} (pulso de {})", path, n),
+            FrameSource::Generated(recipe) => recipe.describe(),
+        }
+    }
+}
+
+/// Lee el manifiesto: devuelve las texturas declaradas y los errores por línea.
+fn parse_manifest(filename: &str, text: &str) -> (Vec<AssetEntry>, Vec<String>) {
+    let mut entries: Vec<AssetEntry> = Vec::new();
+    let mut errors = Vec::new();
+
+    for (n, line) in text.lines().enumerate() {
+        let line = line.split('#').next().unwrap_or("").trim();
+        if line.is_empty() {
+            continue;
+        }
+        // las opciones nombre=valor pueden ir en cualquier lugar después de la clave
+        let (options, parts): (Vec<&str>, Vec<&str>) = line.split_whitespace().partition(|p| p.contains('='));
+        let parsed = (|| {
+            let (kind, rest) = parts.split_first()?;
+            let (key, rest) = rest.split_first()?;
+            let mut key_chars = key.chars();
+            let key = key_chars.next()?;
+            if key_chars.next().is_some() {
+                return None;
+            }
+            let (source, fps) = match (*kind, rest) {
+                ("texture", [path]) => (FrameSource::Single(path.to_string()), 0.0),
+                ("strip", [path, count, fps]) => (FrameSource::Strip(path.to_string(), count.parse().ok()?), fps.parse().ok()?),
+                ("pulse", [path, count, fps]) => (FrameSource::Pulse(path.to_string(), count.parse().ok()?), fps.parse().ok()?),
+                ("frames", [fps, paths @ ..]) if !paths.is_empty() => (
+                    FrameSource::Sequence(paths.iter().map(|p| p.to_string()).collect()),
+                    fps.parse().ok()?,
+                ),
+                ("generate", [pattern, seed]) => (
+                    FrameSource::Generated(Recipe::new(Pattern::parse(pattern)?, seed.parse().ok()?)),
+                    0.0,
+                ),
+                _ => return None,
+            };
+            let mut entry = AssetEntry { key, source, fps, transparency: Transparency::AlphaTest, fallback: None };
+            for option in &options {
+                let (name, value) = option.split_once('=')?;
+                match (name, &mut entry.source) {
+                    ("alpha", _) => entry.transparency = parse_transparency(value)?,
+                    ("else", _) => entry.fallback = Some(parse_recipe(value)?),
+                    (_, FrameSource::Generated(recipe)) => {
+                        if !recipe.set(name, value) {
+                            return None;
+                        }
+                    }
+                    _ => return None,
+                }
+            }
+            Some(entry)
+        })();
+
+        match parsed {
+            Some(entry) => {
+                if entries.iter().any(|e| e.key == entry.key) {
+                    errors.push(format!("{}:{}: la clave '{}' ya estaba declarada; se usa la última", filename, n + 1, entry.key));
+                    entries.retain(|e| e.key != entry.key);
+                }
+                entries.push(entry);
+            }
+            None => errors.push(format!("{}:{}: línea inválida: {}", filename, n + 1, line)),
+        }
+    }
+
+    (entries, errors)
+}
+
+/// Lee el valor de `alpha=`: `test`, `blend`, `key` (con TRANSPARENT_COLOR) o `key:R,G,B`.
+fn parse_transparency(value: &str) -> Option<Transparency> {
+    match value {
+        "test" => Some(Transparency::AlphaTest),
+        "blend" => Some(Transparency::AlphaBlend),
+        "key" => Some(Transparency::ColorKey(TRANSPARENT_COLOR)),
+        _ => procgen::parse_color(value.strip_prefix("key:")?).map(Transparency::ColorKey),
+    }
+}
+
+/// Lee el valor de `else=`: `<dibujo>:<semilla>` (p.ej. `bricks:3`), con los parámetros por defecto.
+fn parse_recipe(value: &str) -> Option<Recipe> {
+    let (pattern, seed) = value.split_once(':')?;
+    Some(Recipe::new(Pattern::parse(pattern)?, seed.parse().ok()?))
+}
+
+/// Fecha de modificación del archivo (None si no existe).
+fn modified(path: &str) -> Option<SystemTime> {
+    fs::metadata(path).and_then(|m| m.modified()).ok()
+}
+
+/// Tablero magenta y negro para texturas que faltan: imposible de confundir con arte de verdad.
+fn placeholder_frames() -> TextureFrames {
+    const SIZE: i32 = 64;
+    const CHECK: i32 = 8;
+    let colors = (0..SIZE * SIZE)
+        .map(|i| {
+            let (x, y) = (i % SIZE, i / SIZE);
+            if (x / CHECK + y / CHECK) % 2 == 0 { Color::new(255, 0, 255, 255) } else { Color::BLACK }
+        })
+        .collect();
+    TextureFrames {
+        frames: vec![build_mip_chain(colors, SIZE, SIZE)],
+        fps: 0.0,
+        transparency: Transparency::AlphaTest,
+        visible: (0, 0, SIZE, SIZE),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn builtin_manifest_has_no_errors() {
+        let (entries, errors) = parse_manifest("textures.txt", BUILTIN_MANIFEST);
+        assert!(errors.is_empty(), "{:?}", errors);
+        assert!(entries.iter().any(|e| e.key == 'e'));
+    }
+
+    #[test]
+    fn parses_every_line_kind_and_option() {
+        let text = "\
+# comentario
+texture a assets/a.png else=bricks:2   # al final también
+strip   b assets/b.png 4 12 alpha=blend
+pulse   c assets/c.png 6 8
+frames  d 10 assets/d1.png assets/d2.png assets/d3.png
+generate e tiles 7 size=32 accent=1,2,3 alpha=key:9,8,7
+";
+        let (entries, errors) = parse_manifest("m", text);
+        assert!(errors.is_empty(), "{:?}", errors);
+        assert_eq!(entries.iter().map(|e| e.key).collect::<String>(), "abcde");
+
+        assert!(matches!(&entries[0].source, FrameSource::Single(p) if p == "assets/a.png"));
+        assert_eq!(entries[0].fallback, Some(Recipe::new(Pattern::Bricks, 2)));
+        assert_eq!(entries[0].transparency, Transparency::AlphaTest);
+
+        assert!(matches!(&entries[1].source, FrameSource::Strip(p, 4) if p == "assets/b.png"));
+        assert_eq!((entries[1].fps, entries[1].transparency), (12.0, Transparency::AlphaBlend));
+        assert!(matches!(&entries[2].source, FrameSource::Pulse(_, 6)));
+        assert!(matches!(&entries[3].source, FrameSource::Sequence(paths) if paths.len() == 3));
+        assert_eq!(entries[3].fps, 10.0);
+
+        let FrameSource::Generated(recipe) = &entries[4].source else {
+            panic!("se esperaba una textura generada");
+        };
+        assert_eq!((recipe.pattern, recipe.seed, recipe.size), (Pattern::Tiles, 7, 32));
+        assert_eq!(recipe.accent, Color::new(1, 2, 3, 255));
+        assert_eq!(entries[4].transparency, Transparency::ColorKey(Color::new(9, 8, 7, 255)));
+    }
+
+    #[test]
+    fn reports_bad_lines_and_duplicates() {
+        let text = "\
+texture ab assets/x.png
+texture a
+strip s assets/s.png muchos 12
+generate g marble 1
+generate h noise 1 size=-3
+texture t assets/t.png size=32
+texture k assets/k.png alpha=sometimes
+texture z assets/1.png
+texture z assets/2.png
+";
+        let (entries, errors) = parse_manifest("m", text);
+        assert_eq!(errors.len(), 8, "{:?}", errors);
+        assert!(errors[0].starts_with("m:1:"));
+        assert!(errors[7].contains("'z'"));
+        // de la clave repetida queda la última
+        assert_eq!(entries.len(), 1);
+        assert!(matches!(&entries[0].source, FrameSource::Single(p) if p == "assets/2.png"));
+    }
+
+    #[test]
+    fn mip_chain_halves_down_to_one_texel() {
+        let colors = vec![Color::WHITE; 8 * 2];
+        let chain = build_mip_chain(colors, 8, 2);
+        let sizes: Vec<(i32, i32)> = chain.iter().map(|l| (l.w, l.h)).collect();
+        assert_eq!(sizes, [(8, 2), (4, 1), (2, 1), (1, 1)]);
+    }
+
+    #[test]
+    fn mip_average_ignores_transparent_texels() {
+        let colors = vec![Color::new(200, 100, 0, 255), Color::new(0, 0, 0, 0), Color::new(200, 100, 0, 255), Color::new(0, 0, 0, 0)];
+        let chain = build_mip_chain(colors, 2, 2);
+        // el color no se oscurece por los texeles transparentes; el alpha sí baja a la mitad
+        assert_eq!(chain[1].colors[0], Color::new(200, 100, 0, 127));
+    }
+
+    #[test]
+    fn transparency_modes_resolve_texels() {
+        let half = Color::new(10, 20, 30, 100);
+        let solid = Color::new(10, 20, 30, 200);
+        assert_eq!(Transparency::AlphaTest.resolve(half), None);
+        assert_eq!(Transparency::AlphaTest.resolve(solid), Some(Color { a: 255, ..solid }));
+        assert_eq!(Transparency::AlphaBlend.resolve(half), Some(half));
+        assert_eq!(Transparency::AlphaBlend.resolve(Color::BLANK), None);
+        assert_eq!(Transparency::ColorKey(TRANSPARENT_COLOR).resolve(solid), Some(Color { a: 255, ..solid }));
+    }
+
+    #[test]
+    fn color_key_becomes_transparent_and_trims_visible_rect() {
+        let key = Color::new(1, 2, 3, 255);
+        let mut colors = vec![key; 4 * 4];
+        colors[5] = Color::RED; // (1, 1)
+        colors[10] = Color::RED; // (2, 2)
+        let tex = build_frames(vec![(colors, 4, 4)], 0.0, Transparency::ColorKey(key));
+        assert_eq!(tex.frames[0][0].colors[0].a, 0);
+        assert_eq!(tex.visible, (1, 1, 2, 2));
+    }
+
+    /// Manifiesto temporal con el contenido `text`; devuelve su ruta. El nombre lleva el del
+    /// test y el id del proceso, así no se pisan tests en paralelo ni dos `cargo test` a la vez.
+    fn temp_manifest(name: &str, text: &str) -> String {
+        let file = format!("laberinto_{}_{}.txt", std::process::id(), name);
+        let path = std::env::temp_dir().join(file);
+        fs::write(&path, text).unwrap();
+        path.to_string_lossy().into_owned()
+    }
+
+    #[test]
+    fn missing_files_fall_back_and_are_reported() {
+        let path = temp_manifest(
+            "missing_files_fall_back",
+            "texture a no/existe.png else=noise:1\ntexture b no/existe.png\ngenerate c tiles 1 size=16\n",
+        );
+        let manager = TextureManager::from_manifest(&path);
+        fs::remove_file(&path).unwrap();
+        let problems = manager.problems();
+        assert_eq!(problems.len(), 2, "{:?}", problems);
+
+        // con else= se usa la generada; sin él, el tablero magenta
+        assert_eq!(manager.size('a'), Some((64, 64)));
+        assert_eq!(manager.get_pixel_color('b', 0, 0), Color::new(255, 0, 255, 255));
+        assert_eq!(manager.size('c'), Some((16, 16)));
+        // clave que no está en el manifiesto: también el tablero
+        assert_eq!(manager.get_pixel_color('?', 8, 0), Color::BLACK);
+    }
+
+    #[test]
+    fn registered_textures_survive_and_animation_follows_time() {
+        let path = temp_manifest("registered_textures_survive", "generate a noise 1 size=8\n");
+        let mut manager = TextureManager::from_manifest(&path);
+        manager.register('r', Recipe::new(Pattern::Blob, 3), Transparency::AlphaBlend);
+        assert_eq!(manager.transparency('r'), Transparency::AlphaBlend);
+        manager.load_all();
+        assert_eq!(manager.transparency('r'), Transparency::AlphaBlend);
+
+        // textura de 3 cuadros a 2 fps armada a mano
+        let frames = (0..3).map(|f| (vec![Color::new(f, 0, 0, 255); 4], 2, 2)).collect();
+        manager.images.insert('z', build_frames(frames, 2.0, Transparency::AlphaTest));
+        assert_eq!(manager.frame_at('z', 0.0), 0);
+        assert_eq!(manager.frame_at('z', 0.6), 1);
+        assert_eq!(manager.frame_at('z', 1.4), 2);
+        assert_eq!(manager.frame_at('z', 1.6), 0);
+        assert_eq!(manager.sample_at('z', 0.6, 0.0, 0.0, 1.0).r, 1);
+        assert_eq!(manager.frame_at('a', 10.0), 0);
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn manifest_changes_are_reloaded() {
+        let path = temp_manifest("manifest_changes_are_reloaded", "generate a noise 1 size=8\n");
+        let mut manager = TextureManager::from_manifest(&path);
+        assert_eq!(manager.size('a'), Some((8, 8)));
+        assert!(manager.reload_changed().is_empty());
+
+        fs::write(&path, "generate a noise 1 size=16\ngenerate b tiles 2 size=8\n").unwrap();
+        // la fecha se adelanta a mano: dos escrituras en el mismo instante no se distinguirían
+        let later = SystemTime::now() + std::time::Duration::from_secs(10);
+        fs::File::options().write(true).open(&path).unwrap().set_modified(later).unwrap();
+        assert_eq!(manager.reload_changed(), vec!['a', 'b']);
+        assert_eq!(manager.size('a'), Some((16, 16)));
+        assert_eq!(manager.size('b'), Some((8, 8)));
+        assert!(manager.reload_changed().is_empty());
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn animated_assets_of_the_game_load() {
+        // los tests corren desde la raíz del proyecto, donde está assets/
+        let manager = TextureManager::from_manifest("assets/textures.txt");
+        let problems = manager.problems();
+        assert!(!problems.iter().any(|p| p.starts_with("'m'") || p.starts_with("'X'")), "{:?}", problems);
+
+        // tira del monitor: 4 cuadros de 48x36 a 5 fps
+        assert_eq!(manager.size('m'), Some((48, 36)));
+        assert_eq!(manager.frame_at('m', 0.1), 0);
+        assert_eq!(manager.frame_at('m', 0.5), 2);
+        assert_eq!(manager.frame_at('m', 0.9), 0);
+        // cartel de salida: 8 archivos a 8 fps, el sexto es el apagado
+        assert_eq!(manager.size('X'), Some((64, 24)));
+        assert_eq!(manager.frame_at('X', 5.0 / 8.0 + 0.01), 5);
+        assert_ne!(manager.sample_at('X', 0.01, 30.0, 12.0, 1.0), manager.sample_at('X', 5.0 / 8.0 + 0.01, 30.0, 12.0, 1.0));
+    }
+}